@@ -1,4 +1,4 @@
--- This file was @generated by Tarmac. It is not intended for manual editing.
+-- This file was @generated by Runway. It is not intended for manual editing.
 return {
 	logo = "rbxassetid://15231990932",
 }
\ No newline at end of file
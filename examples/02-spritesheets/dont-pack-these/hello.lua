@@ -1,2 +1,2 @@
--- This file was @generated by Tarmac. It is not intended for manual editing.
+-- This file was @generated by Runway. It is not intended for manual editing.
 return "rbxassetid://6529931699"
\ No newline at end of file
@@ -1,4 +1,4 @@
--- This file was @generated by Tarmac. It is not intended for manual editing.
+-- This file was @generated by Runway. It is not intended for manual editing.
 return {
 	H = {
 		Image = "rbxassetid://6529931885",
@@ -1,4 +1,4 @@
--- This file was @generated by Tarmac. It is not intended for manual editing.
+-- This file was @generated by Runway. It is not intended for manual editing.
 return {
 	hello = function(dpiScale)
 		if dpiScale >= 3 then
@@ -6,21 +6,21 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-/// Represents a disambiguated and cleaned up path to an asset from a Tarmac
+/// Represents a disambiguated and cleaned up path to an asset from a Runway
 /// project.
 ///
 /// This is really just a string, but by making it have an explicit type with
-/// known conversions, we can avoid some kinds of error trying to use Tarmac
+/// known conversions, we can avoid some kinds of error trying to use Runway
 /// APIs.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct AssetName(Arc<str>);
 
 impl AssetName {
-    pub fn from_paths(root_path: &Path, asset_path: &Path) -> Self {
-        let relative = asset_path
-            .strip_prefix(root_path)
-            .expect("AssetName::from_paths expects asset_path to have root_path as a prefix.");
+    /// Names the asset at the given path by its path relative to the root
+    /// path. Returns `None` if the asset isn't inside the root path.
+    pub fn from_paths(root_path: &Path, asset_path: &Path) -> Option<Self> {
+        let relative = asset_path.strip_prefix(root_path).ok()?;
 
         let displayed = format!("{}", relative.display());
 
@@ -32,7 +32,7 @@ impl AssetName {
             displayed.replace(path::MAIN_SEPARATOR, "/")
         };
 
-        AssetName(displayed.into())
+        Some(AssetName(displayed.into()))
     }
 
     #[cfg(test)]
@@ -0,0 +1,584 @@
+//! Defines how Tarmac generates Lua code for linking to assets.
+//!
+//! Tarmac uses a small Lua AST to build up generated code.
+
+mod model;
+mod naming;
+mod resolver;
+
+use std::{
+    collections::BTreeMap,
+    fmt, io,
+    path::{self, Path, PathBuf},
+};
+
+use fs_err as fs;
+use thiserror::Error;
+
+use crate::{
+    data::ImageSlice,
+    data::{AssetId, CodegenDpiMode, CodegenFormat, CodegenModelStyle, InputConfig, SyncInput},
+    lua_ast::{Block, Expression, Function, IfBlock, Statement, Table},
+};
+
+const CODEGEN_HEADER: &str =
+    "-- This file was @generated by Runway. It is not intended for manual editing.";
+
+/// The outcome of performing codegen for a group of inputs.
+#[derive(Debug, Default)]
+pub struct CodegenReport {
+    /// The files that were written.
+    pub files_written: Vec<PathBuf>,
+
+    /// Problems with individual inputs that didn't stop codegen, like inputs
+    /// that were left out because they were never uploaded.
+    pub errors: Vec<CodegenError>,
+}
+
+/// A folder that generated files are moved into, keeping the paths they would
+/// have had relative to another folder. This keeps the code generated for
+/// each profile apart.
+#[derive(Debug, Clone, Copy)]
+pub struct CodegenFolder<'a> {
+    /// The folder that generated files would otherwise be written under.
+    pub from: &'a Path,
+
+    /// The folder that generated files are written under instead.
+    pub to: &'a Path,
+}
+
+impl CodegenFolder<'_> {
+    fn relocate(self, path: &Path) -> Result<PathBuf, CodegenError> {
+        let relative =
+            path.strip_prefix(self.from)
+                .map_err(|_| CodegenError::OutsideCodegenFolder {
+                    path: path.to_owned(),
+                    folder: self.from.to_owned(),
+                })?;
+
+        Ok(self.to.join(relative))
+    }
+}
+
+/// Generates code for a group of inputs that share the same codegen options.
+///
+/// Files whose contents haven't changed are left untouched, so that tools
+/// watching them aren't triggered needlessly. Inputs marked for codegen that
+/// don't have an asset ID are left out of the generated code and reported
+/// instead of failing the whole group.
+pub fn perform_codegen(
+    output_path: Option<&Path>,
+    codegen_folder: Option<CodegenFolder<'_>>,
+    format: CodegenFormat,
+    model_style: CodegenModelStyle,
+    dpi_mode: CodegenDpiMode,
+    inputs: &[&SyncInput],
+) -> Result<CodegenReport, CodegenError> {
+    let mut report = CodegenReport::default();
+    let inputs = validate_inputs(inputs, &mut report.errors);
+
+    let output_path = output_path
+        .map(|path| output_file(path.to_owned(), codegen_folder))
+        .transpose()?;
+
+    report.files_written = match (output_path, format) {
+        (Some(path), CodegenFormat::Lua) => codegen_grouped(&path, dpi_mode, &inputs)?,
+        (Some(path), _) => model::codegen_grouped(&path, format, model_style, dpi_mode, &inputs)?,
+        (None, CodegenFormat::Lua) => codegen_individual(codegen_folder, &inputs)?,
+        (None, _) => model::codegen_individual(codegen_folder, format, model_style, &inputs)?,
+    };
+
+    Ok(report)
+}
+
+/// Finds where a generated file should be written, moving it into the given
+/// codegen folder if there is one.
+fn output_file(
+    path: PathBuf,
+    codegen_folder: Option<CodegenFolder<'_>>,
+) -> Result<PathBuf, CodegenError> {
+    match codegen_folder {
+        Some(codegen_folder) => codegen_folder.relocate(&path),
+        None => Ok(path),
+    }
+}
+
+/// Checks that no two inputs in a grouped codegen file would be generated with
+/// the same key, without generating anything. Unlike `perform_codegen`, this
+/// works before inputs have been uploaded.
+pub fn check_grouped_keys(inputs: &[&SyncInput]) -> Result<(), CodegenError> {
+    // Grouping only looks at paths and config, so any ID will do.
+    let placeholder_id = AssetId::Id(0);
+
+    let inputs: Vec<_> = inputs
+        .iter()
+        .map(|input| CodegenInput {
+            path: &input.path,
+            path_without_dpi_scale: &input.path_without_dpi_scale,
+            dpi_scale: input.dpi_scale,
+            config: &input.config,
+            id: &placeholder_id,
+            slice: None,
+        })
+        .collect();
+
+    group_inputs(&inputs).map(|_| ())
+}
+
+/// The parts of a `SyncInput` that codegen needs. These are only created for
+/// inputs that have an asset ID, so generating code from them can't fail.
+#[derive(Clone, Copy)]
+struct CodegenInput<'a> {
+    path: &'a Path,
+    path_without_dpi_scale: &'a Path,
+    dpi_scale: u32,
+    config: &'a InputConfig,
+    id: &'a AssetId,
+    slice: Option<ImageSlice>,
+}
+
+/// Pulls the data codegen needs out of each input, skipping inputs that don't
+/// have an asset ID. This usually means that they failed to upload.
+///
+/// Skipped inputs that were marked for codegen are added to `errors`. When
+/// only some DPI variants of an image are skipped, the remaining variants are
+/// still generated and the nearest scale is used in their place.
+fn validate_inputs<'a>(
+    inputs: &[&'a SyncInput],
+    errors: &mut Vec<CodegenError>,
+) -> Vec<CodegenInput<'a>> {
+    let mut valid = Vec::with_capacity(inputs.len());
+
+    for &input in inputs {
+        let Some(id) = &input.id else {
+            if input.config.codegen {
+                errors.push(CodegenError::MissingId {
+                    path: input.path.clone(),
+                });
+            }
+
+            continue;
+        };
+
+        valid.push(CodegenInput {
+            path: &input.path,
+            path_without_dpi_scale: &input.path_without_dpi_scale,
+            dpi_scale: input.dpi_scale,
+            config: &input.config,
+            id,
+            slice: input.slice,
+        });
+    }
+
+    valid
+}
+
+/// Tree used to track and group inputs hierarchically, before turning them into
+/// Lua tables.
+enum GroupedItem<'a> {
+    Folder {
+        children_by_name: BTreeMap<String, GroupedItem<'a>>,
+    },
+    InputGroup {
+        inputs_by_dpi_scale: BTreeMap<u32, CodegenInput<'a>>,
+    },
+}
+
+impl<'a> GroupedItem<'a> {
+    /// Finds any input contained in this item, used to refer to it in errors.
+    fn first_input(&self) -> Option<CodegenInput<'a>> {
+        match self {
+            GroupedItem::Folder { children_by_name } => {
+                children_by_name.values().find_map(GroupedItem::first_input)
+            }
+            GroupedItem::InputGroup {
+                inputs_by_dpi_scale,
+            } => inputs_by_dpi_scale.values().next().copied(),
+        }
+    }
+}
+
+/// Perform codegen for a group of inputs who have `codegen_path` defined.
+///
+/// We'll build up a Lua file containing nested tables that match the structure
+/// of the input's path with its base path stripped away.
+fn codegen_grouped(
+    output_path: &Path,
+    dpi_mode: CodegenDpiMode,
+    inputs: &[CodegenInput<'_>],
+) -> Result<Vec<PathBuf>, CodegenError> {
+    let root_item = group_inputs(inputs)?;
+    let source = grouped_source(&root_item, dpi_mode);
+
+    let mut files_written = Vec::new();
+    if write_if_changed(output_path, source.as_bytes())? {
+        files_written.push(output_path.to_owned());
+    }
+
+    Ok(files_written)
+}
+
+/// Renders the Lua source for a tree of grouped inputs.
+fn grouped_source(root_item: &GroupedItem<'_>, dpi_mode: CodegenDpiMode) -> String {
+    match dpi_mode {
+        CodegenDpiMode::Function => lua_source(&Statement::Return(build_item(root_item))),
+        CodegenDpiMode::Resolver => lua_source(&resolver::resolver_module(root_item)),
+    }
+}
+
+/// Group together the inputs marked for codegen into a tree according to
+/// their paths relative to their codegen base path.
+///
+/// Fails if two different inputs would end up with the same key.
+fn group_inputs<'a>(inputs: &[CodegenInput<'a>]) -> Result<GroupedItem<'a>, CodegenError> {
+    let mut root_folder: BTreeMap<String, GroupedItem<'_>> = BTreeMap::new();
+
+    // First, collect all of the inputs and group them together into a tree
+    // according to their relative paths.
+    for &input in inputs {
+        // Not all inputs will be marked for codegen. We can eliminate those
+        // right away.
+        if !input.config.codegen {
+            continue;
+        }
+
+        // The extension portion of the path is not useful for code generation.
+        // By stripping it off, we generate the names that users expect.
+        let mut path_without_extension = input.path_without_dpi_scale.to_owned();
+        path_without_extension.set_extension("");
+
+        // If we can't construct a relative path, there isn't a sensible name
+        // that we can use to refer to this input.
+        let relative_path = path_without_extension
+            .strip_prefix(&input.config.codegen_base_path)
+            .expect("Input base path was not a base path for input");
+
+        // Collapse `..` path segments so that we can map this path onto our
+        // tree of inputs.
+        let mut segments = Vec::new();
+        for component in relative_path.components() {
+            match component {
+                path::Component::Prefix(_)
+                | path::Component::RootDir
+                | path::Component::Normal(_) => {
+                    segments.push(component.as_os_str().to_str().unwrap())
+                }
+                path::Component::CurDir => {}
+                path::Component::ParentDir => assert!(segments.pop().is_some()),
+            }
+        }
+
+        let keys = input_keys(input, &segments);
+
+        // Navigate down the tree, creating any folder entries that don't exist
+        // yet.
+        let mut current_dir = &mut root_folder;
+        for (i, key) in keys.iter().enumerate() {
+            if i == keys.len() - 1 {
+                // We assume that the last segment of a path must be a file.
+
+                let input_group =
+                    current_dir
+                        .entry(key.clone())
+                        .or_insert_with(|| GroupedItem::InputGroup {
+                            inputs_by_dpi_scale: BTreeMap::new(),
+                        });
+
+                match input_group {
+                    GroupedItem::InputGroup {
+                        inputs_by_dpi_scale,
+                    } => {
+                        // DPI variants of the same image share a key, but
+                        // anything else landing here is a different file.
+                        let existing = inputs_by_dpi_scale.values().next();
+                        if let Some(&existing) = existing {
+                            if existing.path_without_dpi_scale != input.path_without_dpi_scale {
+                                return Err(key_collision(&keys[..=i], existing, input));
+                            }
+                        }
+
+                        inputs_by_dpi_scale.insert(input.dpi_scale, input);
+                    }
+                    GroupedItem::Folder { .. } => {
+                        let existing = input_group.first_input().unwrap();
+                        return Err(key_collision(&keys[..=i], existing, input));
+                    }
+                }
+            } else {
+                if let Some(existing @ GroupedItem::InputGroup { .. }) = current_dir.get(key) {
+                    let existing = existing.first_input().unwrap();
+                    return Err(key_collision(&keys[..=i], existing, input));
+                }
+
+                let next_entry =
+                    current_dir
+                        .entry(key.clone())
+                        .or_insert_with(|| GroupedItem::Folder {
+                            children_by_name: BTreeMap::new(),
+                        });
+
+                if let GroupedItem::Folder { children_by_name } = next_entry {
+                    current_dir = children_by_name;
+                } else {
+                    unreachable!();
+                }
+            }
+        }
+    }
+
+    Ok(GroupedItem::Folder {
+        children_by_name: root_folder,
+    })
+}
+
+/// Turns the path segments of an input into keys according to the input's
+/// naming options. Prefixes and suffixes are only stripped from the file name.
+fn input_keys(input: CodegenInput<'_>, segments: &[&str]) -> Vec<String> {
+    let config = input.config;
+
+    segments
+        .iter()
+        .enumerate()
+        .map(|(i, &segment)| {
+            let segment = if i == segments.len() - 1 {
+                naming::strip_affixes(
+                    segment,
+                    config.codegen_strip_prefix.as_deref(),
+                    config.codegen_strip_suffix.as_deref(),
+                )
+            } else {
+                segment
+            };
+
+            naming::apply_key_style(segment, config.codegen_key_style)
+        })
+        .collect()
+}
+
+fn key_collision(
+    keys: &[String],
+    first: CodegenInput<'_>,
+    second: CodegenInput<'_>,
+) -> CodegenError {
+    CodegenError::KeyCollision {
+        key: keys.join("."),
+        first_path: first.path_without_dpi_scale.to_owned(),
+        second_path: second.path_without_dpi_scale.to_owned(),
+    }
+}
+
+/// Build the Lua expression that a grouped item should be represented by.
+fn build_item(item: &GroupedItem<'_>) -> Expression {
+    match item {
+        GroupedItem::Folder { children_by_name } => {
+            let entries = children_by_name
+                .iter()
+                .map(|(name, child)| (name.into(), build_item(child)))
+                .collect();
+
+            Expression::table(entries)
+        }
+        GroupedItem::InputGroup {
+            inputs_by_dpi_scale,
+        } => {
+            if inputs_by_dpi_scale.len() == 1 {
+                // If there is exactly one input in this group, we can
+                // generate code knowing that there are no high DPI variants
+                // to choose from.
+
+                let input = inputs_by_dpi_scale.values().next().unwrap();
+
+                codegen_input(*input)
+            } else {
+                // In this case, we have the same asset in multiple
+                // different DPI scales. We can generate code to pick
+                // between them at runtime.
+                codegen_with_high_dpi_options(inputs_by_dpi_scale)
+            }
+        }
+    }
+}
+
+/// Perform codegen for a group of inputs that don't have `codegen_path`
+/// defined, and so generate individual files.
+fn codegen_individual(
+    codegen_folder: Option<CodegenFolder<'_>>,
+    inputs: &[CodegenInput<'_>],
+) -> Result<Vec<PathBuf>, CodegenError> {
+    let mut files_written = Vec::new();
+
+    for &input in inputs {
+        let ast = Statement::Return(codegen_input(input));
+
+        let path = output_file(
+            input.path.with_extension(CodegenFormat::Lua.extension()),
+            codegen_folder,
+        )?;
+
+        if write_if_changed(&path, lua_source(&ast).as_bytes())? {
+            files_written.push(path);
+        }
+    }
+
+    Ok(files_written)
+}
+
+/// Writes generated contents to the given path, unless the file there already
+/// has exactly those contents. Returns whether the file was written.
+fn write_if_changed(path: &Path, contents: &[u8]) -> io::Result<bool> {
+    match fs::read(path) {
+        Ok(existing) if existing == contents => {
+            log::trace!("{} is unchanged", path.display());
+            return Ok(false);
+        }
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    log::trace!("Writing {}", path.display());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+
+    Ok(true)
+}
+
+/// Renders Lua code as the contents of a generated Lua file.
+fn lua_source(ast: &impl fmt::Display) -> String {
+    format!("{CODEGEN_HEADER}\n{ast}")
+}
+
+fn codegen_input(input: CodegenInput<'_>) -> Expression {
+    match input.slice {
+        Some(slice) => codegen_url_and_slice(input.id, slice),
+        None => codegen_just_asset_url(input.id),
+    }
+}
+
+fn codegen_url_and_slice(id: &AssetId, slice: ImageSlice) -> Expression {
+    let offset = slice.min();
+    let size = slice.size();
+
+    let mut table = Table::new();
+    table.add_entry("Image", id.to_string());
+    table.add_entry(
+        "ImageRectOffset",
+        Expression::Raw(format!("Vector2.new({}, {})", offset.0, offset.1)),
+    );
+
+    table.add_entry(
+        "ImageRectSize",
+        Expression::Raw(format!("Vector2.new({}, {})", size.0, size.1)),
+    );
+
+    Expression::Table(table)
+}
+
+fn codegen_just_asset_url(id: &AssetId) -> Expression {
+    Expression::String(id.to_string())
+}
+
+fn codegen_dpi_option(input: CodegenInput<'_>) -> (Expression, Block) {
+    let condition = Expression::Raw(format!("dpiScale >= {}", input.dpi_scale));
+    let body = Statement::Return(codegen_input(input));
+
+    (condition, body.into())
+}
+
+fn codegen_with_high_dpi_options(inputs: &BTreeMap<u32, CodegenInput<'_>>) -> Expression {
+    let args = "dpiScale".to_owned();
+
+    let mut options_high_to_low = inputs.values().rev().peekable();
+
+    let highest_dpi_option = options_high_to_low.next().unwrap();
+    let (highest_cond, highest_body) = codegen_dpi_option(*highest_dpi_option);
+
+    let mut if_block = IfBlock::new(highest_cond, highest_body);
+
+    while let Some(dpi_option) = options_high_to_low.next() {
+        let (cond, body) = codegen_dpi_option(*dpi_option);
+
+        if options_high_to_low.peek().is_some() {
+            if_block.else_if_blocks.push((cond, body));
+        } else {
+            if_block.else_block = Some(body);
+        }
+    }
+
+    let statements = vec![Statement::If(if_block)];
+
+    Expression::Function(Function::new(args, statements))
+}
+
+#[derive(Debug, Error)]
+pub enum CodegenError {
+    #[error("Input {} has no asset ID, so it was left out of generated code", .path.display())]
+    MissingId { path: PathBuf },
+
+    #[error(
+        "Inputs {} and {} would both be generated with the key '{key}'",
+        .first_path.display(),
+        .second_path.display()
+    )]
+    KeyCollision {
+        key: String,
+        first_path: PathBuf,
+        second_path: PathBuf,
+    },
+
+    #[error(
+        "Generated file {} isn't inside {}, so it can't be moved into the profile's codegen folder",
+        .path.display(),
+        .folder.display()
+    )]
+    OutsideCodegenFolder { path: PathBuf, folder: PathBuf },
+
+    #[error("Codegen path {} has no file name that's valid Unicode, so it can't be used as an instance name", .path.display())]
+    InvalidName { path: PathBuf },
+
+    #[error(transparent)]
+    XmlModel {
+        #[from]
+        source: rbx_xml::EncodeError,
+    },
+
+    #[error(transparent)]
+    BinaryModel {
+        #[from]
+        source: rbx_binary::EncodeError,
+    },
+
+    #[error(transparent)]
+    Io {
+        #[from]
+        source: io::Error,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn relocate_into_codegen_folder() {
+        let codegen_folder = CodegenFolder {
+            from: Path::new("project"),
+            to: Path::new("project/generated/dev"),
+        };
+
+        assert_eq!(
+            output_file(PathBuf::from("project/ui/Assets.lua"), Some(codegen_folder)).unwrap(),
+            Path::new("project/generated/dev/ui/Assets.lua")
+        );
+        assert_eq!(
+            output_file(PathBuf::from("project/ui/Assets.lua"), None).unwrap(),
+            Path::new("project/ui/Assets.lua")
+        );
+        assert!(matches!(
+            output_file(PathBuf::from("elsewhere/Assets.lua"), Some(codegen_folder)),
+            Err(CodegenError::OutsideCodegenFolder { .. })
+        ));
+    }
+}
@@ -0,0 +1,370 @@
+//! Codegen backend that produces Roblox model files instead of Lua source, so
+//! that generated modules can be inserted into Roblox Studio directly.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use rbx_dom_weak::{
+    types::{Attributes, Vector2},
+    InstanceBuilder, WeakDom,
+};
+
+use super::{
+    build_item, codegen_input, group_inputs, grouped_source, lua_source, output_file,
+    write_if_changed, CodegenError, CodegenFolder, CodegenInput, GroupedItem,
+};
+use crate::{
+    data::{CodegenDpiMode, CodegenFormat, CodegenModelStyle, ImageSlice},
+    lua_ast::Statement,
+};
+
+/// Perform codegen for a group of inputs who have `codegen_path` defined,
+/// creating a single model file at that path.
+pub(super) fn codegen_grouped(
+    output_path: &Path,
+    format: CodegenFormat,
+    model_style: CodegenModelStyle,
+    dpi_mode: CodegenDpiMode,
+    inputs: &[CodegenInput<'_>],
+) -> Result<Vec<PathBuf>, CodegenError> {
+    let root_item = group_inputs(inputs)?;
+    let instance = grouped_instance(output_path, model_style, dpi_mode, &root_item)?;
+
+    let mut files_written = Vec::new();
+    if write_model(output_path, format, instance)? {
+        files_written.push(output_path.to_owned());
+    }
+
+    Ok(files_written)
+}
+
+/// Perform codegen for a group of inputs that don't have `codegen_path`
+/// defined, creating one model file next to each input.
+pub(super) fn codegen_individual(
+    codegen_folder: Option<CodegenFolder<'_>>,
+    format: CodegenFormat,
+    model_style: CodegenModelStyle,
+    inputs: &[CodegenInput<'_>],
+) -> Result<Vec<PathBuf>, CodegenError> {
+    let mut files_written = Vec::new();
+
+    for &input in inputs {
+        let instance = individual_instance(model_style, input)?;
+
+        let path = output_file(
+            input.path.with_extension(format.extension()),
+            codegen_folder,
+        )?;
+        if write_model(&path, format, instance)? {
+            files_written.push(path);
+        }
+    }
+
+    Ok(files_written)
+}
+
+/// Builds the instance that a grouped model file at the given path contains.
+fn grouped_instance(
+    output_path: &Path,
+    model_style: CodegenModelStyle,
+    dpi_mode: CodegenDpiMode,
+    root_item: &GroupedItem<'_>,
+) -> Result<InstanceBuilder, CodegenError> {
+    let name = instance_name(output_path)?;
+
+    Ok(match model_style {
+        CodegenModelStyle::ModuleScript => InstanceBuilder::new("ModuleScript")
+            .with_name(name)
+            .with_property("Source", grouped_source(root_item, dpi_mode)),
+        CodegenModelStyle::ModuleTree | CodegenModelStyle::Values => {
+            build_instance(name, root_item, model_style)
+        }
+    })
+}
+
+/// Builds the instance that the model file generated next to an input
+/// contains.
+fn individual_instance(
+    model_style: CodegenModelStyle,
+    input: CodegenInput<'_>,
+) -> Result<InstanceBuilder, CodegenError> {
+    let name = instance_name(input.path)?;
+
+    Ok(match model_style {
+        CodegenModelStyle::ModuleScript | CodegenModelStyle::ModuleTree => {
+            module_script(name, &Statement::Return(codegen_input(input)))
+        }
+        CodegenModelStyle::Values => string_value(name, input),
+    })
+}
+
+/// Build the instance that a grouped item should be represented by in the
+/// `module-tree` and `values` model styles.
+fn build_instance(
+    name: &str,
+    item: &GroupedItem<'_>,
+    model_style: CodegenModelStyle,
+) -> InstanceBuilder {
+    match item {
+        GroupedItem::Folder { children_by_name } => {
+            let children = children_by_name
+                .iter()
+                .map(|(name, child)| build_instance(name, child, model_style));
+
+            InstanceBuilder::new("Folder")
+                .with_name(name)
+                .with_children(children)
+        }
+        GroupedItem::InputGroup {
+            inputs_by_dpi_scale,
+        } => {
+            if model_style == CodegenModelStyle::Values {
+                string_value_with_dpi_options(name, inputs_by_dpi_scale)
+            } else {
+                module_script(name, &Statement::Return(build_item(item)))
+            }
+        }
+    }
+}
+
+/// Creates a `StringValue` for the lowest DPI scale of an input, with higher
+/// DPI variants stored as children named after their scale.
+fn string_value_with_dpi_options(
+    name: &str,
+    inputs_by_dpi_scale: &BTreeMap<u32, CodegenInput<'_>>,
+) -> InstanceBuilder {
+    let mut options = inputs_by_dpi_scale.values();
+    let mut instance = string_value(name, *options.next().unwrap());
+
+    for &input in options {
+        instance.add_child(string_value(&format!("@{}x", input.dpi_scale), input));
+    }
+
+    instance
+}
+
+fn string_value(name: &str, input: CodegenInput<'_>) -> InstanceBuilder {
+    let mut instance = InstanceBuilder::new("StringValue")
+        .with_name(name)
+        .with_property("Value", input.id.to_string());
+
+    if let Some(slice) = input.slice {
+        instance.add_property("Attributes", slice_attributes(slice));
+    }
+
+    instance
+}
+
+// Slices are bounded by the maximum spritesheet size, which is far smaller than
+// the range that f32 can represent exactly.
+#[allow(clippy::cast_precision_loss)]
+fn slice_attributes(slice: ImageSlice) -> Attributes {
+    let (offset_x, offset_y) = slice.min();
+    let (size_x, size_y) = slice.size();
+
+    Attributes::new()
+        .with(
+            "ImageRectOffset",
+            Vector2::new(offset_x as f32, offset_y as f32),
+        )
+        .with("ImageRectSize", Vector2::new(size_x as f32, size_y as f32))
+}
+
+fn module_script(name: &str, ast: &Statement) -> InstanceBuilder {
+    InstanceBuilder::new("ModuleScript")
+        .with_name(name)
+        .with_property("Source", lua_source(ast))
+}
+
+/// Generated instances are named after the file they represent, without any
+/// extension. Files without a name that's valid Unicode can't be named.
+fn instance_name(path: &Path) -> Result<&str, CodegenError> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| CodegenError::InvalidName {
+            path: path.to_owned(),
+        })
+}
+
+/// Serializes a model containing the given instance, writing it to the given
+/// path if its contents have changed. Returns whether the file was written.
+fn write_model(
+    path: &Path,
+    format: CodegenFormat,
+    instance: InstanceBuilder,
+) -> Result<bool, CodegenError> {
+    let contents = serialize_model(format, instance)?;
+
+    Ok(write_if_changed(path, &contents)?)
+}
+
+/// Serializes a model file containing only the given instance.
+fn serialize_model(
+    format: CodegenFormat,
+    instance: InstanceBuilder,
+) -> Result<Vec<u8>, CodegenError> {
+    let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+    let model = dom.insert(dom.root_ref(), instance);
+
+    let mut contents = Vec::new();
+
+    match format {
+        CodegenFormat::Rbxmx => rbx_xml::to_writer_default(&mut contents, &dom, &[model])?,
+        CodegenFormat::Rbxm => rbx_binary::to_writer(&mut contents, &dom, &[model])?,
+        CodegenFormat::Lua => unreachable!("Lua codegen does not produce model files"),
+    }
+
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod test {
+    use rbx_dom_weak::{types::Variant, Instance};
+
+    use super::*;
+    use crate::data::{AssetId, InputConfig};
+
+    fn codegen_config() -> InputConfig {
+        toml::from_str("glob = \"**/*.png\"\ncodegen = true").unwrap()
+    }
+
+    fn codegen_input<'a>(
+        path: &'a Path,
+        config: &'a InputConfig,
+        id: &'a AssetId,
+        slice: Option<ImageSlice>,
+    ) -> CodegenInput<'a> {
+        CodegenInput {
+            path,
+            path_without_dpi_scale: path,
+            dpi_scale: 1,
+            config,
+            id,
+            slice,
+        }
+    }
+
+    fn child<'a>(dom: &'a WeakDom, parent: &Instance, name: &str) -> &'a Instance {
+        parent
+            .children()
+            .iter()
+            .map(|&referent| dom.get_by_ref(referent).unwrap())
+            .find(|instance| instance.name == name)
+            .unwrap_or_else(|| panic!("{} has no child named {name}", parent.name))
+    }
+
+    fn string_property<'a>(instance: &'a Instance, property: &str) -> &'a str {
+        match instance.properties.get(property) {
+            Some(Variant::String(value)) => value,
+            other => panic!("expected a string {}, got {:?}", property, other),
+        }
+    }
+
+    #[test]
+    fn values_binary_round_trip() {
+        let config = codegen_config();
+        let (close_id, open_id) = (AssetId::Id(1), AssetId::Id(2));
+        let inputs = [
+            codegen_input(
+                Path::new("icons/close.png"),
+                &config,
+                &close_id,
+                Some(ImageSlice::new((2, 3), (10, 13))),
+            ),
+            codegen_input(Path::new("icons/open.png"), &config, &open_id, None),
+        ];
+
+        let root_item = group_inputs(&inputs).unwrap();
+        let instance = grouped_instance(
+            Path::new("out/Assets.rbxm"),
+            CodegenModelStyle::Values,
+            CodegenDpiMode::Function,
+            &root_item,
+        )
+        .unwrap();
+        let contents = serialize_model(CodegenFormat::Rbxm, instance).unwrap();
+
+        let dom = rbx_binary::from_reader(contents.as_slice()).unwrap();
+        let assets = child(&dom, dom.root(), "Assets");
+        assert_eq!(assets.class, "Folder");
+
+        let icons = child(&dom, assets, "icons");
+        let close = child(&dom, icons, "close");
+        assert_eq!(close.class, "StringValue");
+        assert_eq!(string_property(close, "Value"), "rbxassetid://1");
+
+        let Some(Variant::Attributes(attributes)) = close.properties.get("Attributes") else {
+            panic!("close has no attributes");
+        };
+        assert_eq!(
+            attributes.get("ImageRectOffset"),
+            Some(&Variant::Vector2(Vector2::new(2.0, 3.0)))
+        );
+        assert_eq!(
+            attributes.get("ImageRectSize"),
+            Some(&Variant::Vector2(Vector2::new(8.0, 10.0)))
+        );
+
+        let open = child(&dom, icons, "open");
+        assert_eq!(string_property(open, "Value"), "rbxassetid://2");
+        // Binary models give every instance of a class the same properties,
+        // so images without a slice have empty attributes.
+        assert!(!matches!(
+            open.properties.get("Attributes"),
+            Some(Variant::Attributes(attributes)) if attributes.get("ImageRectOffset").is_some()
+        ));
+    }
+
+    #[test]
+    fn module_tree_xml_round_trip() {
+        let config = codegen_config();
+        let id = AssetId::Id(1);
+        let inputs = [codegen_input(
+            Path::new("icons/close.png"),
+            &config,
+            &id,
+            None,
+        )];
+
+        let root_item = group_inputs(&inputs).unwrap();
+        let instance = grouped_instance(
+            Path::new("out/Assets.rbxmx"),
+            CodegenModelStyle::ModuleTree,
+            CodegenDpiMode::Function,
+            &root_item,
+        )
+        .unwrap();
+        let contents = serialize_model(CodegenFormat::Rbxmx, instance).unwrap();
+
+        let dom = rbx_xml::from_reader_default(contents.as_slice()).unwrap();
+        let assets = child(&dom, dom.root(), "Assets");
+        let close = child(&dom, child(&dom, assets, "icons"), "close");
+        assert_eq!(close.class, "ModuleScript");
+        assert!(string_property(close, "Source").contains("rbxassetid://1"));
+    }
+
+    #[test]
+    fn individual_module_script_xml_round_trip() {
+        let config = codegen_config();
+        let id = AssetId::Id(1);
+        let input = codegen_input(Path::new("icons/close.png"), &config, &id, None);
+
+        let instance = individual_instance(CodegenModelStyle::ModuleScript, input).unwrap();
+        let contents = serialize_model(CodegenFormat::Rbxmx, instance).unwrap();
+
+        let dom = rbx_xml::from_reader_default(contents.as_slice()).unwrap();
+        let close = child(&dom, dom.root(), "close");
+        assert_eq!(close.class, "ModuleScript");
+        assert!(string_property(close, "Source").contains("rbxassetid://1"));
+    }
+
+    #[test]
+    fn unnamed_path() {
+        assert!(matches!(
+            instance_name(Path::new("..")),
+            Err(CodegenError::InvalidName { .. })
+        ));
+    }
+}
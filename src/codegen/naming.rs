@@ -0,0 +1,170 @@
+//! Transforms path segments into the keys used to index generated modules.
+
+use crate::data::CodegenKeyStyle;
+
+/// Strips the configured prefix and suffix from a file name. If stripping
+/// would leave nothing behind, the name is left as-is.
+pub(super) fn strip_affixes<'a>(
+    name: &'a str,
+    prefix: Option<&str>,
+    suffix: Option<&str>,
+) -> &'a str {
+    let mut stripped = name;
+
+    if let Some(prefix) = prefix {
+        stripped = stripped.strip_prefix(prefix).unwrap_or(stripped);
+    }
+
+    if let Some(suffix) = suffix {
+        stripped = stripped.strip_suffix(suffix).unwrap_or(stripped);
+    }
+
+    if stripped.is_empty() {
+        name
+    } else {
+        stripped
+    }
+}
+
+/// Converts a path segment into a key following the given style.
+pub(super) fn apply_key_style(name: &str, style: CodegenKeyStyle) -> String {
+    let words = split_words(name);
+
+    match style {
+        CodegenKeyStyle::Preserve => name.to_owned(),
+        CodegenKeyStyle::PascalCase => words.iter().map(|word| capitalize(word)).collect(),
+        CodegenKeyStyle::CamelCase => words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                if i == 0 {
+                    word.to_lowercase()
+                } else {
+                    capitalize(word)
+                }
+            })
+            .collect(),
+        CodegenKeyStyle::SnakeCase => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+    }
+}
+
+/// Splits a name into words on any non-alphanumeric characters and on
+/// transitions from lowercase to uppercase letters, like in `closeButton`.
+fn split_words(name: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut word_start = None;
+    let mut previous: Option<char> = None;
+
+    for (i, c) in name.char_indices() {
+        if !c.is_alphanumeric() {
+            if let Some(start) = word_start.take() {
+                words.push(&name[start..i]);
+            }
+        } else if let Some(start) = word_start {
+            let is_boundary = c.is_uppercase()
+                && previous
+                    .is_some_and(|previous| previous.is_lowercase() || previous.is_numeric());
+
+            if is_boundary {
+                words.push(&name[start..i]);
+                word_start = Some(i);
+            }
+        } else {
+            word_start = Some(i);
+        }
+
+        previous = Some(c);
+    }
+
+    if let Some(start) = word_start {
+        words.push(&name[start..]);
+    }
+
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn preserve() {
+        assert_eq!(
+            apply_key_style("ic-close_button", CodegenKeyStyle::Preserve),
+            "ic-close_button"
+        );
+    }
+
+    #[test]
+    fn pascal_case() {
+        assert_eq!(
+            apply_key_style("ic-close_button", CodegenKeyStyle::PascalCase),
+            "IcCloseButton"
+        );
+        assert_eq!(
+            apply_key_style("closeButton", CodegenKeyStyle::PascalCase),
+            "CloseButton"
+        );
+        assert_eq!(
+            apply_key_style("icon 24px", CodegenKeyStyle::PascalCase),
+            "Icon24px"
+        );
+    }
+
+    #[test]
+    fn camel_case() {
+        assert_eq!(
+            apply_key_style("ic-close_button", CodegenKeyStyle::CamelCase),
+            "icCloseButton"
+        );
+        assert_eq!(
+            apply_key_style("CloseButton", CodegenKeyStyle::CamelCase),
+            "closeButton"
+        );
+    }
+
+    #[test]
+    fn snake_case() {
+        assert_eq!(
+            apply_key_style("ic-close_button", CodegenKeyStyle::SnakeCase),
+            "ic_close_button"
+        );
+        assert_eq!(
+            apply_key_style("CloseButton2x", CodegenKeyStyle::SnakeCase),
+            "close_button2x"
+        );
+    }
+
+    #[test]
+    fn strip() {
+        assert_eq!(
+            strip_affixes("ic-close_button", Some("ic-"), None),
+            "close_button"
+        );
+        assert_eq!(
+            strip_affixes("close_button_icon", None, Some("_icon")),
+            "close_button"
+        );
+        assert_eq!(strip_affixes("ic-", Some("ic-"), None), "ic-");
+        assert_eq!(
+            strip_affixes("button", Some("ic-"), Some("_icon")),
+            "button"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Generates grouped modules that pick between high DPI variants at runtime
+//! through a single injected helper, instead of a function per asset.
+
+use crate::lua_ast::{Block, Expression, Statement, Table};
+
+use super::{codegen_input, GroupedItem};
+
+/// The helper injected into every resolver module. It picks the variant with
+/// the highest scale that doesn't exceed the requested scale, falling back to
+/// the lowest scale available.
+const RESOLVER_HELPER: &str = r#"
+local Assets = {}
+
+function Assets.get(path, dpiScale)
+	local variants = assets[path]
+	if variants == nil then
+		error(string.format("Unknown asset %q", path), 2)
+	end
+
+	dpiScale = dpiScale or 1
+
+	local bestScale, lowestScale
+	for scale in pairs(variants) do
+		if scale <= dpiScale and (bestScale == nil or scale > bestScale) then
+			bestScale = scale
+		end
+		if lowestScale == nil or scale < lowestScale then
+			lowestScale = scale
+		end
+	end
+
+	return variants[bestScale or lowestScale]
+end
+"#;
+
+/// Builds a module that stores every asset in a flat table keyed by its path,
+/// with `/` separating path segments, and returns the resolver helper.
+pub(super) fn resolver_module(root_item: &GroupedItem<'_>) -> Block {
+    let mut assets = Table::new();
+    collect_variants(root_item, &mut Vec::new(), &mut assets);
+
+    Block::from(vec![
+        Statement::Local("assets".to_owned(), Expression::Table(assets)),
+        Statement::Raw(RESOLVER_HELPER.to_owned()),
+        Statement::Return(Expression::Raw("Assets".to_owned())),
+    ])
+}
+
+fn collect_variants<'a>(item: &'a GroupedItem<'_>, path: &mut Vec<&'a str>, assets: &mut Table) {
+    match item {
+        GroupedItem::Folder { children_by_name } => {
+            for (name, child) in children_by_name {
+                path.push(name);
+                collect_variants(child, path, assets);
+                path.pop();
+            }
+        }
+        GroupedItem::InputGroup {
+            inputs_by_dpi_scale,
+        } => {
+            let mut variants = Table::new();
+
+            for (dpi_scale, &input) in inputs_by_dpi_scale {
+                variants.add_entry(
+                    Expression::Raw(format!("[{dpi_scale}]")),
+                    codegen_input(input),
+                );
+            }
+
+            assets.add_entry(path.join("/"), variants);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        codegen::{group_inputs, lua_source, CodegenInput},
+        data::{AssetId, InputConfig},
+    };
+
+    fn codegen_input<'a>(
+        path: &'a str,
+        path_without_dpi_scale: &'a str,
+        dpi_scale: u32,
+        config: &'a InputConfig,
+        id: &'a AssetId,
+    ) -> CodegenInput<'a> {
+        CodegenInput {
+            path: Path::new(path),
+            path_without_dpi_scale: Path::new(path_without_dpi_scale),
+            dpi_scale,
+            config,
+            id,
+            slice: None,
+        }
+    }
+
+    #[test]
+    fn variants_keyed_by_path_and_scale() {
+        let config: InputConfig = toml::from_str("glob = \"**/*.png\"\ncodegen = true").unwrap();
+        let ids = [AssetId::Id(1), AssetId::Id(2), AssetId::Id(3)];
+        let inputs = [
+            codegen_input("icons/logo.png", "icons/logo.png", 1, &config, &ids[0]),
+            codegen_input("icons/logo@2x.png", "icons/logo.png", 2, &config, &ids[1]),
+            codegen_input("close.png", "close.png", 1, &config, &ids[2]),
+        ];
+
+        let root_item = group_inputs(&inputs).unwrap();
+        let source = lua_source(&resolver_module(&root_item));
+
+        let expected_assets = [
+            "local assets = {",
+            "\tclose = {",
+            "\t\t[1] = \"rbxassetid://3\",",
+            "\t},",
+            "\t[\"icons/logo\"] = {",
+            "\t\t[1] = \"rbxassetid://1\",",
+            "\t\t[2] = \"rbxassetid://2\",",
+            "\t},",
+            "}",
+        ]
+        .join("\n");
+        assert!(source.contains(&expected_assets), "{}", source);
+        assert!(source.contains(RESOLVER_HELPER));
+        assert!(source.trim_end().ends_with("return Assets"));
+    }
+}
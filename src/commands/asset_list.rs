@@ -12,10 +12,11 @@
 use std::collections::BTreeSet;
 use std::env;
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Args;
 use fs_err as fs;
+use serde::Serialize;
 
 use anyhow::Result;
 
@@ -31,7 +32,17 @@ pub struct AssetListOptions {
     pub output: PathBuf,
 }
 
-pub async fn asset_list(_: Global, options: AssetListOptions) -> Result<()> {
+/// The result of writing an asset list, printed when using JSON output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct AssetListResult<'a> {
+    /// The file that the asset list was written to.
+    path: &'a Path,
+
+    asset_ids: &'a BTreeSet<u64>,
+}
+
+pub async fn asset_list(global: Global, options: AssetListOptions) -> Result<()> {
     let project_path = match options.project_path {
         Some(path) => path,
         None => env::current_dir()?,
@@ -47,10 +58,13 @@ pub async fn asset_list(_: Global, options: AssetListOptions) -> Result<()> {
     }
 
     let mut file = BufWriter::new(fs::File::create(&options.output)?);
-    for id in asset_list {
+    for id in &asset_list {
         writeln!(file, "{}", id)?;
     }
     file.flush()?;
 
-    Ok(())
+    global.output_format.print_result(&AssetListResult {
+        path: &options.output,
+        asset_ids: &asset_list,
+    })
 }
@@ -0,0 +1,373 @@
+/*
+ * Copyright (c) 2024 Paradoxum Games
+ * This file is licensed under the Mozilla Public License (MPL-2.0). A copy of it is available in the 'LICENSE' file at the root of the repository.
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use clap::Args;
+use serde::Serialize;
+
+use crate::asset_name::AssetName;
+use crate::codegen::check_grouped_keys;
+use crate::data::{Config, InputConfig, Manifest, SyncInput};
+use crate::dpi_scale;
+use crate::options::Global;
+
+use super::sync::{
+    discover_included_configs, find_input_paths, generate_asset_hash, input_name, is_image_asset,
+    read_manifest,
+};
+
+/// The largest width or height that Roblox accepts for uploaded images.
+const MAX_IMAGE_SIZE: u32 = 1024;
+
+#[derive(Debug, Args)]
+pub struct CheckOptions {
+    /// The path to a Runway config, or a folder containing a Runway project.
+    pub config_path: Option<PathBuf>,
+
+    /// The name of a profile from the root config whose manifest should be
+    /// checked.
+    #[clap(long)]
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Problem {
+    severity: Severity,
+    message: String,
+}
+
+/// Everything that `check` found wrong with a project, printed when using
+/// JSON output.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct CheckReport {
+    problems: Vec<Problem>,
+    errors: Vec<String>,
+}
+
+impl CheckReport {
+    fn error(&mut self, message: String) {
+        self.problems.push(Problem {
+            severity: Severity::Error,
+            message,
+        });
+    }
+
+    fn warning(&mut self, message: String) {
+        self.problems.push(Problem {
+            severity: Severity::Warning,
+            message,
+        });
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| problem.severity == severity)
+            .count()
+    }
+}
+
+// Commands all take the global options by value, so that they're passed along
+// to each of them the same way.
+#[allow(clippy::needless_pass_by_value)]
+pub fn check(global: Global, options: CheckOptions) -> Result<()> {
+    let mut report = CheckReport::default();
+
+    if let Err(err) = check_project(&options, &mut report) {
+        report.errors.push(format!("{err:#}"));
+        global.output_format.print_result(&report)?;
+        return Err(err);
+    }
+
+    for problem in &report.problems {
+        match problem.severity {
+            Severity::Error => log::error!("{}", problem.message),
+            Severity::Warning => log::warn!("{}", problem.message),
+        }
+    }
+
+    global.output_format.print_result(&report)?;
+
+    let error_count = report.count(Severity::Error);
+    let warning_count = report.count(Severity::Warning);
+
+    if error_count > 0 {
+        bail!("'runway check' found {error_count} error(s) and {warning_count} warning(s)");
+    }
+
+    log::info!("'runway check' found no errors and {warning_count} warning(s)");
+
+    Ok(())
+}
+
+/// Reads the project and checks it, adding every problem found to the report.
+fn check_project(options: &CheckOptions, report: &mut CheckReport) -> Result<()> {
+    let fuzzy_config_path = match &options.config_path {
+        Some(path) => path.clone(),
+        None => env::current_dir()?,
+    };
+
+    let root_config = Config::read_from_folder_or_file(&fuzzy_config_path)?;
+    let profile = root_config.resolve_profile(options.profile.as_deref())?;
+    let manifest = read_manifest(&root_config, &profile)?;
+
+    let mut configs = discover_included_configs(&root_config)?;
+    configs.insert(0, root_config);
+
+    let inputs = find_inputs(&configs, report);
+    check_dpi_scales(&inputs, report);
+    check_manifest(&inputs, &manifest, report);
+    check_codegen(&inputs, report);
+
+    report.problems.sort();
+
+    Ok(())
+}
+
+/// Finds the inputs of every config, reporting globs that overlap or don't
+/// match anything and files that can't be uploaded.
+fn find_inputs(configs: &[Config], report: &mut CheckReport) -> BTreeMap<AssetName, SyncInput> {
+    // Find every file matched by each glob, keeping track of which globs
+    // matched it so that overlapping globs can be reported together.
+    let mut matches: BTreeMap<PathBuf, Vec<(&Config, &InputConfig)>> = BTreeMap::new();
+
+    for config in configs {
+        for input_config in &config.inputs {
+            let mut found_any = false;
+
+            for result in find_input_paths(config, input_config) {
+                let path = match result {
+                    Ok(path) => path,
+                    Err(err) => {
+                        report.error(err.to_string());
+                        continue;
+                    }
+                };
+
+                found_any = true;
+                matches
+                    .entry(path)
+                    .or_default()
+                    .push((config, input_config));
+            }
+
+            if !found_any {
+                report.warning(format!(
+                    "Glob '{}' in {} does not match any files",
+                    input_config.glob,
+                    config.file_path.display()
+                ));
+            }
+        }
+    }
+
+    let root_folder = configs[0].folder();
+    let mut inputs = BTreeMap::new();
+
+    for (path, matched_by) in matches {
+        let name = match input_name(root_folder, &path) {
+            Ok(name) => name,
+            Err(err) => {
+                report.error(err.to_string());
+                continue;
+            }
+        };
+
+        if matched_by.len() > 1 {
+            let globs: Vec<_> = matched_by
+                .iter()
+                .map(|(config, input_config)| {
+                    format!("'{}' in {}", input_config.glob, config.file_path.display())
+                })
+                .collect();
+
+            report.error(format!(
+                "{} is matched by more than one glob: {}",
+                path.display(),
+                globs.join(", ")
+            ));
+        }
+
+        if !is_image_asset(&path) {
+            report.warning(format!(
+                "{} is not a file type supported by Runway",
+                path.display()
+            ));
+            continue;
+        }
+
+        match image::image_dimensions(&path) {
+            Ok((width, height)) if width > MAX_IMAGE_SIZE || height > MAX_IMAGE_SIZE => {
+                report.error(format!(
+                    "{} is {}x{}, which is larger than the {}x{} that Roblox supports",
+                    path.display(),
+                    width,
+                    height,
+                    MAX_IMAGE_SIZE,
+                    MAX_IMAGE_SIZE
+                ));
+            }
+            Ok(_) => {}
+            Err(err) => {
+                report.error(format!("{} could not be read: {}", path.display(), err));
+                continue;
+            }
+        }
+
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                report.error(format!("{} could not be read: {}", path.display(), err));
+                continue;
+            }
+        };
+        let hash = generate_asset_hash(&contents);
+        let path_info = dpi_scale::extract_path_info(&path);
+
+        inputs.insert(
+            name.clone(),
+            SyncInput {
+                name,
+                path,
+                path_without_dpi_scale: path_info.path_without_dpi_scale,
+                dpi_scale: path_info.dpi_scale,
+                config: matched_by[0].1.clone(),
+                contents: Some(contents),
+                hash,
+                id: None,
+                slice: None,
+                upload: None,
+            },
+        );
+    }
+
+    inputs
+}
+
+/// Reports inputs that changed since they were last synced, and manifest
+/// entries for files that are no longer inputs.
+fn check_manifest(
+    inputs: &BTreeMap<AssetName, SyncInput>,
+    manifest: &Manifest,
+    report: &mut CheckReport,
+) {
+    for (name, input_manifest) in &manifest.inputs {
+        match inputs.get(name) {
+            Some(input) if input.hash != input_manifest.hash => {
+                report.error(format!(
+                    "{} has changed since it was last synced",
+                    input.path.display()
+                ));
+            }
+            Some(_) => {}
+            None => report.warning(format!(
+                "The manifest has an entry for {name}, which is not an input of this project"
+            )),
+        }
+    }
+}
+
+/// Reports images that have high DPI variants but are missing some of the
+/// scales below their highest one, including the 1x scale.
+fn check_dpi_scales(inputs: &BTreeMap<AssetName, SyncInput>, report: &mut CheckReport) {
+    let mut scales_by_path: BTreeMap<&Path, BTreeSet<u32>> = BTreeMap::new();
+
+    for input in inputs.values() {
+        scales_by_path
+            .entry(&input.path_without_dpi_scale)
+            .or_default()
+            .insert(input.dpi_scale);
+    }
+
+    for (path, scales) in scales_by_path {
+        let highest = scales.iter().next_back().copied().unwrap_or(1);
+        let missing: Vec<_> = (1..highest)
+            .filter(|scale| !scales.contains(scale))
+            .map(|scale| format!("@{scale}x"))
+            .collect();
+
+        if !missing.is_empty() {
+            report.warning(format!(
+                "{} has a @{}x variant but is missing {}",
+                path.display(),
+                highest,
+                missing.join(", ")
+            ));
+        }
+    }
+}
+
+/// Reports codegen that would overwrite other codegen: grouped files whose
+/// inputs disagree on options, inputs that would be grouped with the same key,
+/// and individual files that would be written to the same path.
+fn check_codegen(inputs: &BTreeMap<AssetName, SyncInput>, report: &mut CheckReport) {
+    let mut grouped: BTreeMap<&Path, Vec<&SyncInput>> = BTreeMap::new();
+    let mut individual: BTreeMap<PathBuf, Vec<&SyncInput>> = BTreeMap::new();
+
+    // Inputs without codegen don't generate anything that could collide.
+    for input in inputs.values().filter(|input| input.config.codegen) {
+        if let Some(codegen_path) = &input.config.codegen_path {
+            grouped.entry(codegen_path).or_default().push(input);
+        } else {
+            let output_path = input
+                .path
+                .with_extension(input.config.codegen_format.extension());
+            individual.entry(output_path).or_default().push(input);
+        }
+    }
+
+    for (codegen_path, group) in &grouped {
+        let options = |input: &SyncInput| {
+            (
+                input.config.codegen_format,
+                input.config.codegen_model_style,
+                input.config.codegen_dpi_mode,
+            )
+        };
+
+        let first = options(group[0]);
+        if group.iter().any(|input| options(input) != first) {
+            report.error(format!(
+                "{} is the codegen path for inputs with different codegen options",
+                codegen_path.display()
+            ));
+        }
+
+        if let Err(err) = check_grouped_keys(group) {
+            report.error(err.to_string());
+        }
+    }
+
+    for (output_path, group) in &individual {
+        if grouped.contains_key(output_path.as_path()) {
+            report.error(format!(
+                "{} would be generated both for {} and as a grouped codegen file",
+                output_path.display(),
+                group[0].path.display()
+            ));
+        } else if group.len() > 1 {
+            report.error(format!(
+                "{} would be generated for both {} and {}",
+                output_path.display(),
+                group[0].path.display(),
+                group[1].path.display()
+            ));
+        }
+    }
+}
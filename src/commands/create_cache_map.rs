@@ -12,12 +12,13 @@
 use std::collections::BTreeMap;
 use std::env;
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Result};
 use clap::Args;
 use fs_err as fs;
 use resolve_path::PathResolveExt;
+use serde::Serialize;
 
 use crate::asset_name::AssetName;
 use crate::auth_cookie::get_auth_cookie;
@@ -38,6 +39,16 @@ pub struct CreateCacheMapOptions {
     pub index_file: PathBuf,
 }
 
+/// The result of creating a cache map, printed when using JSON output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct CreateCacheMapResult<'a> {
+    index_file: &'a Path,
+
+    /// The packed images that were downloaded into the cache directory.
+    downloaded: Vec<PathBuf>,
+}
+
 pub async fn create_cache_map(global: Global, options: CreateCacheMapOptions) -> Result<()> {
     let api_client = get_preferred_client(RobloxCredentials {
         token: global.auth.or_else(get_auth_cookie),
@@ -72,6 +83,7 @@ pub async fn create_cache_map(global: Global, options: CreateCacheMapOptions) ->
     }
 
     let mut index: BTreeMap<u64, String> = BTreeMap::new();
+    let mut downloaded = Vec::new();
     for (id, contributing_assets) in uploaded_inputs {
         if contributing_assets.len() == 1 {
             index.insert(id, contributing_assets[0].to_string());
@@ -81,6 +93,7 @@ pub async fn create_cache_map(global: Global, options: CreateCacheMapOptions) ->
             fs::write(&path, contents)?;
 
             index.insert(id, path.display().to_string());
+            downloaded.push(path);
         }
     }
 
@@ -88,5 +101,8 @@ pub async fn create_cache_map(global: Global, options: CreateCacheMapOptions) ->
     serde_json::to_writer_pretty(&mut file, &index)?;
     file.flush()?;
 
-    Ok(())
+    global.output_format.print_result(&CreateCacheMapResult {
+        index_file: &index_file,
+        downloaded,
+    })
 }
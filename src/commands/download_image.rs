@@ -11,6 +11,7 @@
 
 use clap::Args;
 use fs_err as fs;
+use serde::Serialize;
 
 use crate::{
     auth_cookie::get_auth_cookie,
@@ -28,6 +29,16 @@ pub struct DownloadImageOptions {
     pub output: String,
 }
 
+/// The result of downloading an image, printed when using JSON output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct DownloadImageResult<'a> {
+    asset_id: u64,
+
+    /// The file that the image was written to.
+    path: &'a str,
+}
+
 pub async fn download_image(global: Global, options: DownloadImageOptions) -> anyhow::Result<()> {
     let client = get_preferred_client(RobloxCredentials {
         token: global.auth.or_else(get_auth_cookie),
@@ -37,7 +48,10 @@ pub async fn download_image(global: Global, options: DownloadImageOptions) -> an
     })?;
 
     let response = client.download_image(options.asset_id).await?;
-    fs::write(options.output, response)?;
+    fs::write(&options.output, response)?;
 
-    Ok(())
+    global.output_format.print_result(&DownloadImageResult {
+        asset_id: options.asset_id,
+        path: &options.output,
+    })
 }
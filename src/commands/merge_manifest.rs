@@ -0,0 +1,330 @@
+/*
+ * Copyright (c) 2024 Paradoxum Games
+ * This file is licensed under the Mozilla Public License (MPL-2.0). A copy of it is available in the 'LICENSE' file at the root of the repository.
+*/
+
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use clap::Args;
+use fs_err as fs;
+
+use crate::asset_name::AssetName;
+use crate::data::{InputManifest, Manifest};
+use crate::options::Global;
+
+use super::sync::generate_asset_hash;
+
+#[derive(Debug, Args)]
+pub struct MergeManifestOptions {
+    /// The manifest from the common ancestor of both branches (`%O` in a git
+    /// merge driver).
+    pub base: PathBuf,
+
+    /// The manifest from the current branch (`%A`). The merged manifest is
+    /// written back to this path.
+    pub ours: PathBuf,
+
+    /// The manifest from the branch being merged in (`%B`).
+    pub theirs: PathBuf,
+
+    /// Where the manifest being merged is in the repository (`%P`). `<ours>`
+    /// is usually a temporary file, so this is used to find the project.
+    pub path: Option<PathBuf>,
+
+    /// The folder containing the project that the manifest belongs to, used to
+    /// find inputs on disk. Defaults to the folder containing `<path>`, or the
+    /// folder containing `<ours>` if no path is given.
+    #[clap(long)]
+    pub project_path: Option<PathBuf>,
+}
+
+/// How an input that was changed differently on both branches was merged.
+enum Resolution<'a> {
+    /// The entry whose hash matches the input on disk was picked.
+    Resolved(Option<&'a InputManifest>),
+
+    /// Neither entry matches the input on disk, so our entry was kept.
+    Unresolved,
+}
+
+/// The result of merging two manifests.
+struct Merge {
+    manifest: Manifest,
+
+    /// Inputs that changed differently on both branches, where neither entry
+    /// matches the file on disk. Our entries are kept for them.
+    unresolved: Vec<AssetName>,
+
+    /// Inputs that were packed into a spritesheet on either branch and changed
+    /// differently on both, so they need to be packed again.
+    needs_resync: Vec<AssetName>,
+}
+
+pub fn merge_manifest(_: Global, options: MergeManifestOptions) -> Result<()> {
+    let project_path = if let Some(path) = options.project_path {
+        path
+    } else {
+        let manifest_path = options.path.as_ref().unwrap_or(&options.ours);
+        manifest_path
+            .parent()
+            .map_or_else(PathBuf::new, Path::to_path_buf)
+    };
+
+    let base = read_base(&options.base)?;
+    let ours = Manifest::read_from_file(&options.ours)?;
+    let theirs = Manifest::read_from_file(&options.theirs)?;
+
+    let merge = merge(&project_path, &base, &ours, &theirs)?;
+    merge.manifest.write_to_file(&options.ours)?;
+
+    for name in &merge.needs_resync {
+        log::warn!(
+            "{name} was packed into a different spritesheet on each branch. Run a sync to pack it again."
+        );
+    }
+
+    if !merge.unresolved.is_empty() {
+        for name in &merge.unresolved {
+            log::error!("Could not merge {name}: neither branch's entry matches the file on disk");
+        }
+
+        bail!(
+            "{} manifest entries could not be merged automatically",
+            merge.unresolved.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Reads the manifest from the common ancestor. Git passes an empty file when
+/// both branches added the manifest, which is read as a manifest without any
+/// inputs.
+fn read_base(path: &Path) -> Result<Manifest> {
+    if fs::metadata(path)?.len() == 0 {
+        return Ok(Manifest::default());
+    }
+
+    Ok(Manifest::read_from_file(path)?)
+}
+
+/// Merges the entries of two manifests that were changed from a common base.
+/// Entries that only changed on one side take that side's change, and entries
+/// that changed on both are resolved against the files in the project.
+fn merge(
+    project_path: &Path,
+    base: &Manifest,
+    ours: &Manifest,
+    theirs: &Manifest,
+) -> Result<Merge> {
+    let names: BTreeSet<&AssetName> = base
+        .inputs
+        .keys()
+        .chain(ours.inputs.keys())
+        .chain(theirs.inputs.keys())
+        .collect();
+
+    let mut merge = Merge {
+        manifest: Manifest::default(),
+        unresolved: Vec::new(),
+        needs_resync: Vec::new(),
+    };
+
+    for name in names {
+        let base_entry = base.inputs.get(name);
+        let our_entry = ours.inputs.get(name);
+        let their_entry = theirs.inputs.get(name);
+
+        let entry = if our_entry == their_entry || their_entry == base_entry {
+            our_entry.cloned()
+        } else if our_entry == base_entry {
+            their_entry.cloned()
+        } else {
+            let packed = our_entry.is_some_and(|entry| entry.slice.is_some())
+                || their_entry.is_some_and(|entry| entry.slice.is_some());
+
+            if packed {
+                merge.needs_resync.push(name.clone());
+            }
+
+            match resolve_conflict(project_path, name, our_entry, their_entry)? {
+                Resolution::Resolved(entry) => entry.cloned(),
+                Resolution::Unresolved => {
+                    merge.unresolved.push(name.clone());
+                    our_entry.cloned()
+                }
+            }
+        };
+
+        if let Some(entry) = entry {
+            merge.manifest.inputs.insert(name.clone(), entry);
+        }
+    }
+
+    Ok(merge)
+}
+
+/// Picks between two conflicting entries for the same input by checking which
+/// one describes the file that's currently on disk. A missing entry matches a
+/// missing file.
+fn resolve_conflict<'a>(
+    project_path: &Path,
+    name: &AssetName,
+    ours: Option<&'a InputManifest>,
+    theirs: Option<&'a InputManifest>,
+) -> Result<Resolution<'a>> {
+    let disk_hash = match fs::read(project_path.join(name.as_ref())) {
+        Ok(contents) => Some(generate_asset_hash(&contents)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let matches_disk =
+        |entry: Option<&InputManifest>| entry.map(|entry| &entry.hash) == disk_hash.as_ref();
+
+    let resolution = match (matches_disk(ours), matches_disk(theirs)) {
+        (true, _) => Resolution::Resolved(ours),
+        (false, true) => Resolution::Resolved(theirs),
+        (false, false) => Resolution::Unresolved,
+    };
+
+    Ok(resolution)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::ImageSlice;
+    use crate::test_util::TempDir;
+
+    fn entry(hash: &str, id: u64) -> InputManifest {
+        InputManifest {
+            hash: hash.to_owned(),
+            id: Some(id),
+            slice: None,
+            packable: false,
+            upload: None,
+            local: None,
+        }
+    }
+
+    fn manifest(entries: &[(&str, InputManifest)]) -> Manifest {
+        let mut manifest = Manifest::default();
+        for (name, entry) in entries {
+            manifest.inputs.insert(AssetName::new(name), entry.clone());
+        }
+        manifest
+    }
+
+    /// Creates a project folder containing a file with the given name and
+    /// contents.
+    fn project_with_file(folder_name: &str, name: &str, contents: &[u8]) -> TempDir {
+        let folder = TempDir::new(folder_name);
+        fs::write(folder.join(name), contents).unwrap();
+        folder
+    }
+
+    #[test]
+    fn one_sided_changes() {
+        let base = manifest(&[("a.png", entry("a", 1)), ("b.png", entry("b", 2))]);
+        let ours = manifest(&[("a.png", entry("a2", 3)), ("b.png", entry("b", 2))]);
+        let theirs = manifest(&[
+            ("a.png", entry("a", 1)),
+            ("b.png", entry("b2", 4)),
+            ("c.png", entry("c", 5)),
+        ]);
+
+        let merge = merge(Path::new("missing"), &base, &ours, &theirs).unwrap();
+
+        let expected = manifest(&[
+            ("a.png", entry("a2", 3)),
+            ("b.png", entry("b2", 4)),
+            ("c.png", entry("c", 5)),
+        ]);
+        assert_eq!(merge.manifest.inputs, expected.inputs);
+        assert!(merge.unresolved.is_empty());
+        assert!(merge.needs_resync.is_empty());
+    }
+
+    #[test]
+    fn one_sided_removal() {
+        let base = manifest(&[("a.png", entry("a", 1))]);
+        let ours = manifest(&[]);
+        let theirs = base.clone();
+
+        let merge = merge(Path::new("missing"), &base, &ours, &theirs).unwrap();
+
+        assert!(merge.manifest.inputs.is_empty());
+    }
+
+    #[test]
+    fn same_change_on_both_sides() {
+        let base = manifest(&[("a.png", entry("a", 1))]);
+        let ours = manifest(&[("a.png", entry("a2", 2))]);
+        let theirs = ours.clone();
+
+        let merge = merge(Path::new("missing"), &base, &ours, &theirs).unwrap();
+
+        assert_eq!(merge.manifest.inputs, ours.inputs);
+        assert!(merge.unresolved.is_empty());
+    }
+
+    #[test]
+    fn conflict_resolved_by_disk_hash() {
+        let contents = b"theirs";
+        let project_path = project_with_file("resolved", "a.png", contents);
+        let disk_hash = generate_asset_hash(contents);
+
+        let mut their_entry = entry(&disk_hash, 3);
+        their_entry.slice = Some(ImageSlice::new((0, 0), (8, 8)));
+
+        let base = manifest(&[("a.png", entry("a", 1))]);
+        let ours = manifest(&[("a.png", entry("ours", 2))]);
+        let theirs = manifest(&[("a.png", their_entry.clone())]);
+
+        let merge = merge(project_path.path(), &base, &ours, &theirs).unwrap();
+
+        assert_eq!(merge.manifest.inputs[&AssetName::new("a.png")], their_entry);
+        assert!(merge.unresolved.is_empty());
+        assert_eq!(merge.needs_resync, vec![AssetName::new("a.png")]);
+    }
+
+    #[test]
+    fn conflict_resolved_by_missing_file() {
+        let base = manifest(&[("a.png", entry("a", 1))]);
+        let ours = manifest(&[("a.png", entry("ours", 2))]);
+        let theirs = manifest(&[]);
+
+        let merge = merge(Path::new("missing"), &base, &ours, &theirs).unwrap();
+
+        assert!(merge.manifest.inputs.is_empty());
+        assert!(merge.unresolved.is_empty());
+    }
+
+    #[test]
+    fn unresolved_conflict_keeps_ours() {
+        let project_path = project_with_file("unresolved", "a.png", b"neither");
+
+        let base = manifest(&[("a.png", entry("a", 1))]);
+        let ours = manifest(&[("a.png", entry("ours", 2))]);
+        let theirs = manifest(&[("a.png", entry("theirs", 3))]);
+
+        let merge = merge(project_path.path(), &base, &ours, &theirs).unwrap();
+
+        assert_eq!(merge.manifest.inputs, ours.inputs);
+        assert_eq!(merge.unresolved, vec![AssetName::new("a.png")]);
+        assert!(merge.needs_resync.is_empty());
+    }
+
+    #[test]
+    fn empty_base() {
+        let folder = TempDir::new("merge-empty-base");
+        let path = folder.join("base.toml");
+        fs::write(&path, "").unwrap();
+
+        assert!(read_base(&path).unwrap().inputs.is_empty());
+    }
+}
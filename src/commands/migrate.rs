@@ -0,0 +1,247 @@
+/*
+ * Copyright (c) 2024 Paradoxum Games
+ * This file is licensed under the Mozilla Public License (MPL-2.0). A copy of it is available in the 'LICENSE' file at the root of the repository.
+*/
+
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use clap::Args;
+use fs_err as fs;
+
+use crate::data::{Config, CONFIG_FILENAME, LEGACY_CONFIG_FILENAME};
+use crate::options::Global;
+
+use super::sync::discover_included_configs;
+
+#[derive(Debug, Args)]
+pub struct MigrateOptions {
+    /// The path to a config, or a folder containing a project that still uses
+    /// Tarmac's file names. Defaults to the current directory.
+    pub project_path: Option<PathBuf>,
+
+    /// Print the files that would be renamed without renaming them.
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+pub fn migrate(_: Global, options: MigrateOptions) -> Result<()> {
+    let fuzzy_config_path = match options.project_path {
+        Some(path) => path,
+        None => env::current_dir()?,
+    };
+
+    let root_config = Config::read_from_folder_or_file(&fuzzy_config_path)?;
+    let mut configs = discover_included_configs(&root_config)?;
+    configs.insert(0, root_config);
+
+    // Configs that other configs include by their path would no longer be
+    // found once they're renamed, so those includes are updated to match.
+    let mut included_by_path: BTreeMap<PathBuf, Vec<&Config>> = BTreeMap::new();
+    for config in &configs {
+        for include in &config.includes {
+            if include.file_name() == Some(OsStr::new(LEGACY_CONFIG_FILENAME)) {
+                included_by_path
+                    .entry(include.clone())
+                    .or_default()
+                    .push(config);
+            }
+        }
+    }
+
+    let mut renames = Vec::new();
+
+    for config in &configs {
+        if config.file_path.file_name() == Some(OsStr::new(LEGACY_CONFIG_FILENAME)) {
+            let new_path = config.folder().join(CONFIG_FILENAME);
+            renames.push((config.file_path.clone(), new_path));
+        }
+    }
+
+    // Manifests only live next to the root config, both the default one and
+    // the ones for each profile.
+    for entry in fs::read_dir(configs[0].folder())? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().and_then(OsStr::to_str) else {
+            continue;
+        };
+
+        if let Some(rest) = file_name.strip_prefix("tarmac-manifest.") {
+            if rest.ends_with("toml") {
+                let new_path = path.with_file_name(format!("runway-manifest.{rest}"));
+                renames.push((path, new_path));
+            }
+        }
+    }
+
+    if renames.is_empty() {
+        log::info!("Nothing to migrate, this project already uses Runway's file names");
+        return Ok(());
+    }
+
+    // Where each renamed file ends up, so that includes are updated in their
+    // config under its new name.
+    let mut new_paths = BTreeMap::new();
+
+    for (from, to) in renames {
+        if to.exists() {
+            log::warn!(
+                "Skipping {} because {} already exists",
+                from.display(),
+                to.display()
+            );
+            continue;
+        }
+
+        if options.dry_run {
+            log::info!("Would rename {} to {}", from.display(), to.display());
+        } else {
+            fs::rename(&from, &to)?;
+            log::info!("Renamed {} to {}", from.display(), to.display());
+        }
+
+        new_paths.insert(from, to);
+    }
+
+    let mut left_to_user = 0;
+
+    for (include, parents) in &included_by_path {
+        if !new_paths.contains_key(include) {
+            continue;
+        }
+
+        for parent in parents {
+            let parent_path = new_paths
+                .get(&parent.file_path)
+                .unwrap_or(&parent.file_path);
+
+            if options.dry_run {
+                log::info!(
+                    "Would update the include of {} in {}",
+                    include.display(),
+                    parent_path.display()
+                );
+            } else if update_include(parent_path, parent.folder(), include)? {
+                log::info!(
+                    "Updated the include of {} in {}",
+                    include.display(),
+                    parent_path.display()
+                );
+            } else {
+                log::warn!(
+                    "Could not find the include of {} in {}. Update it to {} by hand.",
+                    include.display(),
+                    parent_path.display(),
+                    new_paths[include].display()
+                );
+                left_to_user += 1;
+            }
+        }
+    }
+
+    if left_to_user > 0 {
+        bail!("{left_to_user} include(s) of renamed configs must be updated by hand");
+    }
+
+    Ok(())
+}
+
+/// Rewrites the entries in the config at `config_path` that include the given
+/// legacy config by its path, so that they name the renamed config instead.
+/// Relative entries are relative to `folder`. Returns whether any entry was
+/// rewritten.
+fn update_include(config_path: &Path, folder: &Path, include: &Path) -> Result<bool> {
+    let contents = fs::read_to_string(config_path)?;
+    let value: toml::Value = toml::from_str(&contents)?;
+
+    let entries = value
+        .get("includes")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_str)
+        .filter(|entry| folder.join(entry) == include);
+
+    // The config is edited as text so that its formatting and comments are
+    // kept. Entries are found as they'd be written in either kind of string.
+    let mut new_contents = contents.clone();
+    for entry in entries {
+        let Some(prefix) = entry.strip_suffix(LEGACY_CONFIG_FILENAME) else {
+            continue;
+        };
+        let new_entry = format!("{prefix}{CONFIG_FILENAME}");
+
+        new_contents = new_contents
+            .replace(&basic_string(entry), &basic_string(&new_entry))
+            .replace(&format!("'{entry}'"), &format!("'{new_entry}'"));
+    }
+
+    if new_contents == contents {
+        return Ok(false);
+    }
+
+    fs::write(config_path, new_contents)?;
+    Ok(true)
+}
+
+/// Writes a string the way it'd appear in a TOML basic string.
+fn basic_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn update_include_by_path() {
+        let folder = TempDir::new("migrate-include");
+        let config_path = folder.join(CONFIG_FILENAME);
+        fs::write(
+            &config_path,
+            concat!(
+                "name = \"root\"\n",
+                "# Shared icons\n",
+                "includes = [\"icons/tarmac.toml\", 'other/tarmac.toml', \"fonts\"]\n",
+            ),
+        )
+        .unwrap();
+
+        let updated = update_include(
+            &config_path,
+            folder.path(),
+            &folder.join("icons/tarmac.toml"),
+        )
+        .unwrap();
+
+        assert!(updated);
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            concat!(
+                "name = \"root\"\n",
+                "# Shared icons\n",
+                "includes = [\"icons/runway.toml\", 'other/tarmac.toml', \"fonts\"]\n",
+            )
+        );
+    }
+
+    #[test]
+    fn update_missing_include() {
+        let folder = TempDir::new("migrate-missing-include");
+        let config_path = folder.join(CONFIG_FILENAME);
+        fs::write(&config_path, "name = \"root\"\nincludes = [\"fonts\"]\n").unwrap();
+
+        let updated = update_include(
+            &config_path,
+            folder.path(),
+            &folder.join("icons/tarmac.toml"),
+        )
+        .unwrap();
+
+        assert!(!updated);
+    }
+}
@@ -10,27 +10,46 @@
 */
 
 mod asset_list;
+mod check;
 mod create_cache_map;
 mod download_image;
+mod merge_manifest;
+mod migrate;
+mod schema;
 mod sync;
 mod upload_image;
+mod watch;
 
 pub use asset_list::*;
+pub use check::*;
 use clap::Subcommand;
 pub use create_cache_map::*;
 pub use download_image::*;
+pub use merge_manifest::*;
+pub use migrate::*;
+pub use schema::*;
 pub use sync::*;
 pub use upload_image::*;
+pub use watch::*;
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
-    /// Upload a single image to the Roblox cloud. Prints the asset ID of the
-    /// resulting Image asset to stdout.
+    /// Upload a single image to the Roblox cloud. Logs the asset ID of the
+    /// resulting Image asset, or prints it to stdout as JSON when using
+    /// `--output-format json`.
     UploadImage(UploadImageOptions),
 
-    /// Sync your Tarmac project, uploading any assets that have changed.
+    /// Sync your Runway project, uploading any assets that have changed.
     Sync(SyncOptions),
 
+    /// Sync your Runway project, then keep watching its inputs and configs,
+    /// syncing again whenever they change.
+    Watch(WatchOptions),
+
+    /// Validate your Runway project's config, manifest, and files without
+    /// syncing anything. Exits with an error if any problems are found.
+    Check(CheckOptions),
+
     /// Downloads any packed spritesheets, then generates a file mapping asset
     /// IDs to file paths. This command only works when logged into Roblox
     /// Studio or when a .ROBLOSECURITY token is passed via --auth.
@@ -41,4 +60,17 @@ pub enum Command {
 
     /// Downloads a single image from the Roblox cloud.
     DownloadImage(DownloadImageOptions),
+
+    /// Merges two versions of a manifest with their common ancestor. Intended
+    /// to be used as a git merge driver.
+    MergeManifest(MergeManifestOptions),
+
+    /// Renames a project's config files and manifests, including those of
+    /// included projects, from the names that Tarmac used to the names that
+    /// Runway looks for first.
+    Migrate(MigrateOptions),
+
+    /// Prints a JSON Schema describing Runway config files, which editors can
+    /// use to validate and autocomplete them.
+    Schema(SchemaOptions),
 }
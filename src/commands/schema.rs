@@ -0,0 +1,40 @@
+/*
+ * Copyright (c) 2024 Paradoxum Games
+ * This file is licensed under the Mozilla Public License (MPL-2.0). A copy of it is available in the 'LICENSE' file at the root of the repository.
+*/
+
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use fs_err as fs;
+
+use crate::data::Config;
+use crate::options::Global;
+
+#[derive(Debug, Args)]
+pub struct SchemaOptions {
+    /// A path to a file to write the schema to. If not given, the schema is
+    /// printed to stdout.
+    #[clap(long)]
+    pub output: Option<PathBuf>,
+}
+
+pub fn schema(_: Global, options: SchemaOptions) -> Result<()> {
+    let schema = schemars::schema_for!(Config);
+
+    if let Some(path) = options.output {
+        let mut file = BufWriter::new(fs::File::create(path)?);
+        serde_json::to_writer_pretty(&mut file, &schema)?;
+        writeln!(file)?;
+        file.flush()?;
+    } else {
+        let stdout = io::stdout();
+        let mut handle = stdout.lock();
+        serde_json::to_writer_pretty(&mut handle, &schema)?;
+        writeln!(handle)?;
+    }
+
+    Ok(())
+}
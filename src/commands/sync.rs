@@ -12,50 +12,62 @@
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     env,
+    fs::Metadata,
     io::{self, BufWriter, Write},
+    mem,
     path::{Path, PathBuf},
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 
 use anyhow::{bail, Result};
 use fs_err as fs;
+use ignore::WalkBuilder;
 use image::{
     codecs::png::PngEncoder,
     imageops::{self, resize},
     DynamicImage, GenericImageView, ImageError,
 };
 use packos::{InputItem, SimplePacker};
+use rayon::prelude::*;
+use secrecy::SecretString;
+use serde::Serialize;
 use thiserror::Error;
-use walkdir::WalkDir;
 
 use crate::{
     alpha_bleed::alpha_bleed,
     asset_name::AssetName,
     auth_cookie::get_auth_cookie,
-    codegen::perform_codegen,
+    codegen::{perform_codegen, CodegenError, CodegenFolder},
     data::{
-        AssetId, Config, ConfigError, ImageSlice, InputManifest, Manifest, ManifestError, SyncInput,
+        AssetId, CodegenDpiMode, CodegenFormat, CodegenModelStyle, Config, ConfigError, ImageSlice,
+        InputConfig, InputDefaults, InputManifest, LocalInputManifest, Manifest, ManifestError,
+        ResolvedProfile, StatCache, SyncInput, SyncLock, SyncLockError, SyncedState, UploadRecord,
+        UploadTarget,
     },
     dpi_scale,
-    options::Global,
+    failure::Failure,
+    options::{Global, OutputFormat},
+    progress::Progress,
     roblox_api::{get_preferred_client, RobloxApiClient, RobloxApiError, RobloxCredentials},
     sync_backend::{
         DebugSyncBackend, Error as SyncBackendError, LocalSyncBackend, NoneSyncBackend,
-        RetryBackend, RobloxSyncBackend, SyncBackend, UploadInfo,
+        RetryBackend, RobloxSyncBackend, SyncBackend, UploadCacheBackend, UploadInfo,
+        UploadResponse,
     },
+    upload_cache::open_upload_cache,
 };
 
 use clap::Args;
 
 #[derive(Debug, Args)]
 pub struct SyncOptions {
-    /// Where Tarmac should sync the project.
+    /// Where Runway should sync the project.
     ///
     /// Options:
     ///
     /// - roblox: Upload to Roblox.com
     ///
-    /// - none: Do not upload. Tarmac will exit with an error if there are any
+    /// - none: Do not upload. Runway will exit with an error if there are any
     ///   unsynced assets.
     ///
     /// - debug: Copy to local debug directory for debugging output
@@ -64,7 +76,7 @@ pub struct SyncOptions {
     #[clap(long, require_equals = true, value_enum, num_args = 0..=1, default_value_t = SyncTarget::Roblox)]
     pub target: SyncTarget,
 
-    /// When provided, Tarmac will upload again at most the given number of times
+    /// When provided, Runway will upload again at most the given number of times
     /// when it encounters rate limitation errors.
     #[clap(long)]
     pub retry: Option<usize>,
@@ -73,11 +85,22 @@ pub struct SyncOptions {
     #[clap(long, default_value = "60")]
     pub retry_delay: u64,
 
-    /// The path to a Tarmac config, or a folder containing a Tarmac project.
+    /// The path to a Runway config, or a folder containing a Runway project.
     pub config_path: Option<PathBuf>,
 
+    /// The name of a profile from the root config to sync with. Profiles can
+    /// upload to a different creator and keep their own manifest.
+    #[clap(long)]
+    pub profile: Option<String>,
+
     #[clap(long, value_parser(clap::builder::ValueParser::new(parse_resize_var)))]
     pub resize: Option<(u32, u32)>,
+
+    /// Read and hash every input, instead of trusting the stat cache for
+    /// inputs whose size and modification time haven't changed since the last
+    /// sync.
+    #[clap(long)]
+    pub no_cache: bool,
 }
 
 fn parse_resize_var(env: &str) -> Result<(u32, u32)> {
@@ -99,6 +122,30 @@ pub enum SyncTarget {
     Local,
 }
 
+impl SyncTarget {
+    /// The target recorded in the manifest for uploads made with this target.
+    fn upload_target(self) -> Option<UploadTarget> {
+        match self {
+            SyncTarget::Roblox => Some(UploadTarget::Roblox),
+            SyncTarget::Debug => Some(UploadTarget::Debug),
+            SyncTarget::Local | SyncTarget::None => None,
+        }
+    }
+
+    /// Picks what the manifest remembers about the last time an input was
+    /// synced with this target. Local syncs are tracked separately from
+    /// uploads, so switching between them doesn't force everything to sync
+    /// again.
+    fn previous_state(self, input_manifest: &InputManifest) -> Option<SyncedState<'_>> {
+        match self {
+            SyncTarget::Local => input_manifest.local_state(),
+            SyncTarget::Roblox | SyncTarget::None | SyncTarget::Debug => {
+                Some(input_manifest.uploaded_state())
+            }
+        }
+    }
+}
+
 async fn sync_session(
     session: &mut SyncSession,
     options: &SyncOptions,
@@ -117,66 +164,98 @@ async fn sync_session(
 }
 
 pub async fn sync(global: Global, options: SyncOptions) -> Result<()> {
+    let output_format = global.output_format;
+    let token = global.auth.or_else(get_auth_cookie);
+    let mut session = None;
+
+    if let Err(err) = run_sync(&mut session, &options, token, global.api_key).await {
+        print_report(output_format, session.as_ref(), Some(&err))?;
+        return Err(err);
+    }
+
+    let session = session.expect("the session should be created by a successful sync");
+    print_report(output_format, Some(&session), None)?;
+
+    if session.sync_errors.is_empty() {
+        Ok(())
+    } else {
+        Err(SyncError::HadErrors {
+            error_count: session.sync_errors.len(),
+            failure: Failure::of_sync_errors(&session.sync_errors),
+        }
+        .into())
+    }
+}
+
+/// Creates a session in `session` and syncs it, leaving the session there
+/// even if syncing stops early.
+pub(super) async fn run_sync(
+    session: &mut Option<SyncSession>,
+    options: &SyncOptions,
+    token: Option<SecretString>,
+    api_key: Option<SecretString>,
+) -> Result<()> {
     let fuzzy_config_path = match &options.config_path {
         Some(v) => v.to_owned(),
         None => env::current_dir()?,
     };
 
-    let mut session = SyncSession::new(&fuzzy_config_path)?;
+    let session = session.insert(SyncSession::new(
+        &fuzzy_config_path,
+        options.profile.as_deref(),
+        options.target,
+        !options.no_cache,
+    )?);
 
-    let credentials = RobloxCredentials {
-        token: global.auth.or_else(get_auth_cookie),
-        api_key: global.api_key,
-        group_id: session.root_config().upload_to_group_id,
-        user_id: session.root_config().upload_to_user_id,
-    };
-    let api_client = get_preferred_client(credentials.clone())?;
-
-    let project_name = session.root_config().name.to_string();
     session.discover_configs()?;
     session.discover_inputs()?;
 
-    match &options.target {
-        SyncTarget::Roblox => {
-            let api_client = get_preferred_client(credentials.clone())?;
+    let credentials = session.credentials(token, api_key);
+    session.sync(options, &credentials, None).await?;
 
-            sync_session(
-                &mut session,
-                &options,
-                Box::new(RobloxSyncBackend::new(api_client)),
-            )
-        }
-        SyncTarget::Local => sync_session(
-            &mut session,
-            &options,
-            Box::new(LocalSyncBackend::new(Some(project_name))?),
-        ),
-        SyncTarget::None => sync_session(&mut session, &options, Box::new(NoneSyncBackend)),
-        SyncTarget::Debug => {
-            sync_session(&mut session, &options, Box::new(DebugSyncBackend::new()))
-        }
+    Ok(())
+}
+
+/// Prints what happened during a session's last sync if results are printed
+/// as JSON. Errors that stopped the sync early are reported too, so that tools
+/// reading the output always get a report, even without a session.
+pub(super) fn print_report(
+    output_format: OutputFormat,
+    session: Option<&SyncSession>,
+    error: Option<&anyhow::Error>,
+) -> Result<()> {
+    let mut report = session.map_or_else(SyncReport::default, SyncSession::report);
+
+    if let Some(error) = error {
+        report.errors.push(format!("{error:#}"));
     }
-    .await;
 
-    session.write_manifest()?;
-    session.codegen()?;
-    session.write_asset_list()?;
-    session.populate_asset_cache(api_client).await?;
+    output_format.print_result(&report)
+}
 
-    if session.sync_errors.is_empty() {
-        Ok(())
-    } else {
-        Err(SyncError::HadErrors {
-            error_count: session.sync_errors.len(),
+/// Creates the backend that inputs should be synced to for the given target.
+fn create_backend(
+    target: SyncTarget,
+    credentials: &RobloxCredentials,
+    project_name: &str,
+) -> Result<Box<dyn SyncBackend + Sync + Send + 'static>> {
+    let backend: Box<dyn SyncBackend + Sync + Send + 'static> = match target {
+        SyncTarget::Roblox => {
+            let api_client = get_preferred_client(credentials.clone())?;
+            Box::new(RobloxSyncBackend::new(api_client))
         }
-        .into())
-    }
+        SyncTarget::Local => Box::new(LocalSyncBackend::new(Some(project_name.to_owned()))?),
+        SyncTarget::None => Box::new(NoneSyncBackend),
+        SyncTarget::Debug => Box::new(DebugSyncBackend::new()),
+    };
+
+    Ok(backend)
 }
 
 /// A sync session holds all of the state for a single run of the 'tarmac sync'
-/// command.
+/// command, or for as long as 'tarmac watch' is running.
 #[derive(Debug)]
-struct SyncSession {
+pub(super) struct SyncSession {
     /// The set of all configs known by the sync session.
     ///
     /// This list is always at least one element long. The first entry is the
@@ -184,6 +263,12 @@ struct SyncSession {
     /// SyncSession::root_config to retrieve it.
     configs: Vec<Config>,
 
+    /// The upload options and manifest location picked from the root config.
+    profile: ResolvedProfile,
+
+    /// Where this session is syncing inputs to.
+    target: SyncTarget,
+
     /// The manifest file that was present as of the beginning of the sync
     /// operation.
     original_manifest: Manifest,
@@ -191,20 +276,82 @@ struct SyncSession {
     /// All of the inputs discovered so far in the current sync.
     inputs: BTreeMap<AssetName, SyncInput>,
 
+    /// The paths of inputs that were found in the current sync but couldn't
+    /// be read. Their manifest entries are kept as they were, so that their
+    /// asset IDs aren't lost.
+    unreadable_inputs: BTreeMap<AssetName, PathBuf>,
+
+    /// The hashes of inputs as of the last discovery, or `None` if every input
+    /// should be read and hashed.
+    stat_cache: Option<StatCache>,
+
     /// Errors encountered during syncing that we ignored at the time.
     sync_errors: Vec<anyhow::Error>,
 
+    /// What happened to inputs during the current sync. Inputs without a
+    /// result were unchanged, and skipped.
+    input_results: BTreeMap<AssetName, InputResult>,
+
+    /// The files written during the current sync.
+    files_written: Vec<PathBuf>,
+
+    /// Inputs in the manifest as of the beginning of the current sync that
+    /// weren't found again, and so were removed from it.
+    removed_inputs: Vec<AssetName>,
+
     /// The current sprite's index. Used for `local` to use different file names for each sprite that's used.
     current_sprite_index: u32,
+
+    /// Keeps other syncs from using this session's manifest for as long as
+    /// this session uses it. Also tells where the manifest is written.
+    lock: SyncLock,
 }
 
-/// Contains information to help Tarmac batch process different kinds of assets.
+/// Contains information to help Runway batch process different kinds of assets.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct InputKind {
     packable: bool,
     dpi_scale: u32,
 }
 
+/// What happened to an input during a sync.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+enum InputResult {
+    Uploaded,
+
+    /// The input has the same contents as an image that was already synced,
+    /// so that image's asset was reused.
+    Reused,
+
+    Skipped,
+
+    Failed {
+        error: String,
+    },
+}
+
+/// The result of a sync, printed when using JSON output.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(super) struct SyncReport<'a> {
+    inputs: Vec<InputReport<'a>>,
+    removed: &'a [AssetName],
+    files_written: &'a [PathBuf],
+    errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct InputReport<'a> {
+    name: &'a AssetName,
+    path: &'a Path,
+    id: Option<String>,
+
+    #[serde(flatten)]
+    result: &'a InputResult,
+}
+
 struct PackedImage {
     img: DynamicImage,
     index: u32,
@@ -212,187 +359,482 @@ struct PackedImage {
 }
 
 impl SyncSession {
-    fn new(fuzzy_config_path: &Path) -> Result<Self, SyncError> {
+    pub(super) fn new(
+        fuzzy_config_path: &Path,
+        profile_name: Option<&str>,
+        target: SyncTarget,
+        use_stat_cache: bool,
+    ) -> Result<Self, SyncError> {
         log::trace!("Starting new sync session");
 
         let root_config = Config::read_from_folder_or_file(&fuzzy_config_path)?;
 
         log::trace!("Starting from config \"{}\"", root_config.name);
 
-        let original_manifest = match Manifest::read_from_folder(root_config.folder()) {
-            Ok(manifest) => manifest,
-            Err(err) if err.is_not_found() => Manifest::default(),
-            other => other?,
-        };
+        let profile = root_config.resolve_profile(profile_name)?;
+
+        if let Some(name) = &profile.name {
+            log::debug!("Using profile \"{}\"", name);
+        }
+
+        let manifest_path = manifest_path(&root_config, &profile);
+        let lock = SyncLock::acquire(&manifest_path)?;
+
+        let original_manifest = read_manifest(&root_config, &profile)?;
+        let stat_cache = use_stat_cache.then(|| StatCache::read_from_folder(root_config.folder()));
 
         Ok(Self {
             configs: vec![root_config],
+            profile,
+            target,
             original_manifest,
             inputs: BTreeMap::new(),
+            unreadable_inputs: BTreeMap::new(),
+            stat_cache,
             sync_errors: Vec::new(),
+            input_results: BTreeMap::new(),
+            files_written: Vec::new(),
+            removed_inputs: Vec::new(),
             current_sprite_index: 1,
+            lock,
         })
     }
 
-    /// Raise a sync error that will fail the sync process at a later point.
-    fn raise_error(&mut self, error: impl Into<anyhow::Error>) {
-        let error = error.into();
-        log::error!("{:?}", error);
-        self.sync_errors.push(error);
+    /// The credentials to upload with, using the creator from this session's
+    /// profile.
+    pub(super) fn credentials(
+        &self,
+        token: Option<SecretString>,
+        api_key: Option<SecretString>,
+    ) -> RobloxCredentials {
+        RobloxCredentials {
+            token,
+            api_key,
+            group_id: self.profile.upload_to_group_id,
+            user_id: self.profile.upload_to_user_id,
+        }
     }
 
-    /// The config that this sync session was started from.
-    fn root_config(&self) -> &Config {
-        &self.configs[0]
+    /// Syncs every discovered input, then writes the manifest, generated code,
+    /// and everything else that depends on the synced inputs.
+    ///
+    /// If the inputs from an earlier sync in this session are given, code is
+    /// only generated again for inputs that changed since then.
+    pub(super) async fn sync(
+        &mut self,
+        options: &SyncOptions,
+        credentials: &RobloxCredentials,
+        previous_inputs: Option<&BTreeMap<AssetName, SyncInput>>,
+    ) -> Result<()> {
+        let api_client = get_preferred_client(credentials.clone())?;
+        let backend = create_backend(options.target, credentials, &self.root_config().name)?;
+        let backend = self.with_upload_cache(options.target, backend);
+        sync_session(self, options, backend).await;
+
+        let manifest = self.build_manifest();
+        self.write_manifest(&manifest)?;
+
+        self.removed_inputs = self
+            .original_manifest
+            .inputs
+            .keys()
+            .filter(|name| {
+                !self.inputs.contains_key(*name) && !self.unreadable_inputs.contains_key(*name)
+            })
+            .cloned()
+            .collect();
+
+        // Later syncs in the same session start from what was just synced.
+        self.original_manifest = manifest;
+
+        let affected = previous_inputs.and_then(|previous| affected_inputs(previous, &self.inputs));
+        self.codegen(affected.as_ref())?;
+        self.write_asset_list()?;
+        self.populate_asset_cache(api_client).await?;
+        self.log_summary();
+
+        Ok(())
     }
 
-    /// Locate all of the configs connected to our root config.
-    ///
-    /// Tarmac config files can include each other via the `includes` field,
-    /// which will search the given path for other config files and use them as
-    /// part of the sync.
-    fn discover_configs(&mut self) -> Result<()> {
-        let mut to_search = VecDeque::new();
-        to_search.extend(self.root_config().includes.iter().cloned());
+    /// Wraps the backend with the root config's upload cache, if it has one.
+    /// Uploads are cached by creator, so the cache isn't used if it isn't
+    /// known who images are uploaded to. Only uploads to Roblox are cached,
+    /// since other targets write to this machine and their IDs would mean
+    /// nothing to anyone else sharing the cache.
+    fn with_upload_cache(
+        &self,
+        target: SyncTarget,
+        backend: Box<dyn SyncBackend + Sync + Send + 'static>,
+    ) -> Box<dyn SyncBackend + Sync + Send + 'static> {
+        let root_config = self.root_config();
+        let Some(location) = &root_config.upload_cache else {
+            return backend;
+        };
 
-        while let Some(search_path) = to_search.pop_front() {
-            let search_meta = fs::metadata(&search_path)?;
+        if target.upload_target() != Some(UploadTarget::Roblox) {
+            return backend;
+        }
 
-            if search_meta.is_file() {
-                // This is a file that's explicitly named by a config. We'll
-                // check that it's a Tarmac config and include it.
+        let creator = match (
+            self.profile.upload_to_group_id,
+            self.profile.upload_to_user_id,
+        ) {
+            (Some(group_id), _) => format!("group-{group_id}"),
+            (None, Some(user_id)) => format!("user-{user_id}"),
+            (None, None) => {
+                log::warn!(
+                    "The upload cache is only used when uploading to a group or to a user set with 'upload-to-user-id'"
+                );
+                return backend;
+            }
+        };
 
-                let config = Config::read_from_file(&search_path)?;
+        let cache = open_upload_cache(location, root_config.folder());
+        Box::new(UploadCacheBackend::new(backend, cache, creator))
+    }
 
-                // Include any configs that this config references.
-                to_search.extend(config.includes.iter().cloned());
+    /// Reads all of the configs again, starting from the given path, and
+    /// picks the profile with the given name from the new root config.
+    pub(super) fn reload_configs(
+        &mut self,
+        fuzzy_config_path: &Path,
+        profile_name: Option<&str>,
+    ) -> Result<()> {
+        let root_config = Config::read_from_folder_or_file(fuzzy_config_path)?;
+        let profile = root_config.resolve_profile(profile_name)?;
+
+        // The reloaded configs can point to another manifest. It's locked
+        // before it's read, and the old one stays locked until then.
+        let manifest_path = manifest_path(&root_config, &profile);
+        if manifest_path != self.lock.manifest_path() {
+            log::debug!("Switching to manifest {}", manifest_path.display());
+
+            let lock = SyncLock::acquire(&manifest_path)?;
+            self.original_manifest = read_manifest(&root_config, &profile)?;
+            self.lock = lock;
+        }
 
-                self.configs.push(config);
-            } else {
-                // If this directory contains a config file, we can stop
-                // traversing this branch.
+        self.profile = profile;
+        self.configs = vec![root_config];
 
-                match Config::read_from_folder(&search_path) {
-                    Ok(config) => {
-                        // We found a config, we're done here.
+        self.discover_configs()
+    }
 
-                        // Append config include paths from this config
-                        to_search.extend(config.includes.iter().cloned());
+    /// Prepares this session to discover and sync inputs again. Returns the
+    /// inputs from the last sync so that they can be compared with the ones
+    /// found next.
+    pub(super) fn prepare_resync(&mut self) -> BTreeMap<AssetName, SyncInput> {
+        self.sync_errors.clear();
+        self.input_results.clear();
+        self.files_written.clear();
+        self.unreadable_inputs.clear();
+
+        // Spritesheets are numbered from 1 again, like in a new session, so
+        // that local syncs write over the ones from the last sync instead of
+        // adding to them.
+        self.current_sprite_index = 1;
+
+        mem::take(&mut self.inputs)
+    }
 
-                        self.configs.push(config);
-                    }
+    pub(super) fn configs(&self) -> &[Config] {
+        &self.configs
+    }
 
-                    Err(err) if err.is_not_found() => {
-                        // We didn't find a config, keep searching down this
-                        // branch of the filesystem.
+    pub(super) fn inputs(&self) -> &BTreeMap<AssetName, SyncInput> {
+        &self.inputs
+    }
 
-                        let children = fs::read_dir(&search_path)?;
+    pub(super) fn sync_errors(&self) -> &[anyhow::Error] {
+        &self.sync_errors
+    }
 
-                        for entry in children {
-                            let entry = entry?;
-                            let entry_path = entry.path();
+    /// Describes what happened to every input during the current sync.
+    pub(super) fn report(&self) -> SyncReport<'_> {
+        let synced = self.inputs.iter().map(|(name, input)| InputReport {
+            name,
+            path: &input.path,
+            id: input.id.as_ref().map(ToString::to_string),
+            result: self
+                .input_results
+                .get(name)
+                .unwrap_or(&InputResult::Skipped),
+        });
+
+        // Inputs that couldn't be read keep the ID they had before.
+        let unreadable = self
+            .unreadable_inputs
+            .iter()
+            .map(|(name, path)| InputReport {
+                name,
+                path,
+                id: self
+                    .original_manifest
+                    .inputs
+                    .get(name)
+                    .and_then(|original| self.target.previous_state(original))
+                    .and_then(|previous| previous.id)
+                    .map(|id| id.to_string()),
+                result: &self.input_results[name],
+            });
+
+        let inputs = synced.chain(unreadable).collect();
+
+        SyncReport {
+            inputs,
+            removed: &self.removed_inputs,
+            files_written: &self.files_written,
+            errors: self
+                .sync_errors
+                .iter()
+                .map(|err| format!("{err:#}"))
+                .collect(),
+        }
+    }
 
-                            // DirEntry has a metadata method, but in the case
-                            // of symlinks, it returns metadata about the
-                            // symlink and not the file or folder.
-                            let entry_meta = fs::metadata(&entry_path)?;
+    /// Logs a table of how many inputs were uploaded, reused, skipped, failed,
+    /// and removed, followed by the reason each failed input failed.
+    fn log_summary(&self) {
+        use std::fmt::Write as _;
 
-                            if entry_meta.is_dir() {
-                                to_search.push_back(entry_path);
-                            }
-                        }
-                    }
+        let (mut uploaded, mut reused, mut skipped) = (0, 0, 0);
+        let mut failed = Vec::new();
 
-                    Err(err) => {
-                        return Err(err.into());
-                    }
-                }
+        for name in self.inputs.keys().chain(self.unreadable_inputs.keys()) {
+            match self.input_results.get(name) {
+                Some(InputResult::Uploaded) => uploaded += 1,
+                Some(InputResult::Reused) => reused += 1,
+                Some(InputResult::Failed { error }) => failed.push((name, error)),
+                Some(InputResult::Skipped) | None => skipped += 1,
+            }
+        }
+
+        let rows = [
+            ("Uploaded", uploaded),
+            ("Reused", reused),
+            ("Skipped", skipped),
+            ("Failed", failed.len()),
+            ("Removed", self.removed_inputs.len()),
+        ];
+
+        let mut summary = String::from("Sync summary:");
+        for (label, count) in rows {
+            write!(summary, "\n{label:<10}{count:>6}").unwrap();
+        }
+        log::info!("{summary}");
+
+        if !failed.is_empty() {
+            let width = failed
+                .iter()
+                .map(|(name, _)| name.as_ref().len())
+                .max()
+                .unwrap_or(0);
+
+            let mut message = String::from("Failed inputs:");
+            for (name, error) in failed {
+                write!(message, "\n{:<width$}  {error}", name.as_ref()).unwrap();
             }
+            log::error!("{message}");
+        }
+
+        if !self.removed_inputs.is_empty() {
+            let mut message = String::from("Removed inputs:");
+            for name in &self.removed_inputs {
+                write!(message, "\n{name}").unwrap();
+            }
+            log::info!("{message}");
+        }
+    }
+
+    /// Records that the given inputs failed to sync, unless they were already
+    /// synced before the error happened.
+    fn record_failure(&mut self, input_names: &[AssetName], error: &anyhow::Error) {
+        for input_name in input_names {
+            self.input_results
+                .entry(input_name.clone())
+                .or_insert_with(|| InputResult::Failed {
+                    error: format!("{error:#}"),
+                });
         }
+    }
+
+    /// Raise a sync error that will fail the sync process at a later point.
+    fn raise_error(&mut self, error: impl Into<anyhow::Error>) {
+        let error = error.into();
+        log::error!("{:?}", error);
+        self.sync_errors.push(error);
+    }
+
+    /// The config that this sync session was started from.
+    fn root_config(&self) -> &Config {
+        &self.configs[0]
+    }
+
+    /// Locate all of the configs connected to our root config.
+    pub(super) fn discover_configs(&mut self) -> Result<()> {
+        let included = discover_included_configs(self.root_config())?;
+        self.configs.extend(included);
 
         Ok(())
     }
 
     /// Find all files on the filesystem referenced as inputs by our configs.
-    fn discover_inputs(&mut self) -> Result<()> {
-        let inputs = &mut self.inputs;
+    pub(super) fn discover_inputs(&mut self) -> Result<()> {
+        let target = self.target;
         let root_config_path = &self.configs[0].folder();
+        let stat_cache = self.stat_cache.as_ref();
+        let mut new_stat_cache = StatCache::default();
+
+        // Inputs that can't be found or read are reported once discovery is
+        // done, so that one bad file doesn't keep every other input from
+        // syncing.
+        let mut errors: Vec<anyhow::Error> = Vec::new();
 
         // Starting with our root config, iterate over all configs and find all
         // relevant inputs
-        for config in &self.configs {
-            let config_path = config.folder();
+        let mut found = Vec::new();
+        let discovering = Progress::new("discover", None);
 
+        for config in &self.configs {
             for input_config in &config.inputs {
-                let base_path = config_path.join(input_config.glob.get_prefix());
-                log::trace!(
-                    "Searching for inputs in '{}' matching '{}'",
-                    base_path.display(),
-                    input_config.glob,
-                );
-
-                let filtered_paths = WalkDir::new(base_path)
-                    .into_iter()
-                    // TODO: Properly handle WalkDir errors
-                    .filter_map(Result::ok)
-                    .filter(|entry| {
-                        let match_path = entry.path().strip_prefix(config_path).unwrap();
-                        input_config.glob.is_match(match_path)
-                    });
-
-                for matching in filtered_paths {
-                    let path = matching.into_path();
-
-                    let name = AssetName::from_paths(root_config_path, &path);
-                    log::trace!("Found input {}", name);
-
-                    let path_info = dpi_scale::extract_path_info(&path);
-
-                    let contents = fs::read(&path)?;
-                    let hash = generate_asset_hash(&contents);
+                for result in find_input_paths(config, input_config) {
+                    match result {
+                        Ok(path) => {
+                            found.push((input_config, path));
+                            discovering.inc(1);
+                        }
+                        Err(err) => errors.push(SyncError::from(err).into()),
+                    }
+                }
+            }
+        }
 
-                    // If this input was known during the last sync operation,
-                    // pull the information we knew about it out.
-                    let (id, slice) = match self.original_manifest.inputs.get(&name) {
-                        Some(original) => (original.id.map(AssetId::Id), original.slice),
-                        None => (None, None),
-                    };
+        drop(discovering);
+
+        // Reading and hashing files is spread across threads. Collecting keeps
+        // the order that inputs were found in, so errors and logs are the same
+        // on every run.
+        let hashing = Progress::new("hash", Some(found.len() as u64));
+        let read_results: Vec<_> = found
+            .par_iter()
+            .map(|(_, path)| {
+                let read_result = read_input(stat_cache, path);
+                hashing.inc(1);
+                read_result
+            })
+            .collect();
+        drop(hashing);
+
+        for ((input_config, path), read_result) in found.into_iter().zip(read_results) {
+            let name = match input_name(root_config_path, &path) {
+                Ok(name) => name,
+                Err(err) => {
+                    errors.push(err.into());
+                    continue;
+                }
+            };
+            log::trace!("Found input {}", name);
 
-                    let already_found = inputs.insert(
+            let (metadata, contents, hash) = match read_result {
+                Ok(read) => read,
+                Err(err) => {
+                    let err = anyhow::Error::from(SyncError::from(err));
+                    self.input_results.insert(
                         name.clone(),
-                        SyncInput {
-                            name,
-                            path,
-                            path_without_dpi_scale: path_info.path_without_dpi_scale,
-                            dpi_scale: path_info.dpi_scale,
-                            config: input_config.clone(),
-                            contents,
-                            hash,
-                            id,
-                            slice,
+                        InputResult::Failed {
+                            error: format!("{err:#}"),
                         },
                     );
+                    self.unreadable_inputs.insert(name, path);
+                    errors.push(err);
+                    continue;
+                }
+            };
+            if let Some(metadata) = &metadata {
+                new_stat_cache.insert(path.clone(), metadata, hash.clone());
+            }
 
-                    if let Some(existing) = already_found {
-                        return Err(SyncError::OverlappingGlobs {
-                            path: existing.path,
-                        }
-                        .into());
-                    }
+            let path_info = dpi_scale::extract_path_info(&path);
+
+            // If this input was known during the last sync operation, pull the
+            // information we knew about it out.
+            let original = self.original_manifest.inputs.get(&name);
+            let (id, slice) = match original.and_then(|original| target.previous_state(original)) {
+                Some(previous) => (previous.id, previous.slice),
+                None => (None, None),
+            };
+
+            // Local syncs don't upload anything, so uploads are only relevant
+            // to other targets.
+            let upload = match target {
+                SyncTarget::Local => None,
+                _ => original.and_then(|original| original.upload.clone()),
+            };
+
+            let already_found = self.inputs.insert(
+                name.clone(),
+                SyncInput {
+                    name,
+                    path,
+                    path_without_dpi_scale: path_info.path_without_dpi_scale,
+                    dpi_scale: path_info.dpi_scale,
+                    config: input_config.clone(),
+                    contents,
+                    hash,
+                    id,
+                    slice,
+                    upload,
+                },
+            );
+
+            if let Some(existing) = already_found {
+                return Err(SyncError::OverlappingGlobs {
+                    path: existing.path,
                 }
+                .into());
             }
         }
 
+        self.update_stat_cache(new_stat_cache);
+
+        for err in errors {
+            self.raise_error(err);
+        }
+
         Ok(())
     }
 
+    /// Replaces the stat cache with one describing the inputs that were just
+    /// discovered. Failing to save it only makes the next sync slower, so it
+    /// isn't treated as an error.
+    fn update_stat_cache(&mut self, new_stat_cache: StatCache) {
+        let Some(stat_cache) = &mut self.stat_cache else {
+            return;
+        };
+
+        if *stat_cache == new_stat_cache {
+            return;
+        }
+
+        if let Err(err) = new_stat_cache.write_to_folder(self.configs[0].folder()) {
+            log::warn!("Could not save the stat cache: {err}");
+        }
+
+        *stat_cache = new_stat_cache;
+    }
+
     async fn sync_with_backend(&mut self, options: &SyncOptions, backend: Box<dyn SyncBackend>) {
         let mut compatible_input_groups = BTreeMap::new();
 
+        // Shared by every group of unpackable images, so that images uploaded
+        // for one group can be reused by the next.
+        let mut synced_assets =
+            synced_assets_by_hash(&self.original_manifest, self.target, options.resize);
+
         for (input_name, input) in &self.inputs {
             if !is_image_asset(&input.path) {
                 log::warn!(
-                    "Asset '{}' is not recognized by Tarmac.",
+                    "Asset '{}' is not recognized by Runway.",
                     input.path.display()
                 );
 
@@ -411,72 +853,195 @@ impl SyncSession {
 
         'outer: for (kind, group) in compatible_input_groups {
             if kind.packable {
-                if let Err(err) = self.sync_packable_images(&options, &backend, group).await {
-                    if err.is::<SyncError>() {
-                        let err = err.downcast::<SyncError>().unwrap();
-                        let rate_limited = err.is_rate_limited();
+                if let Err(err) = self.sync_packable_images(&options, &backend, &group).await {
+                    self.record_failure(&group, &err);
 
-                        println!("{}: {:#?}", rate_limited, err);
-                        self.raise_error(err);
+                    let rate_limited = Failure::of(&err) == Failure::RateLimited;
+                    self.raise_error(err);
 
-                        if rate_limited {
-                            break 'outer;
-                        }
-                    } else {
-                        self.raise_error(err);
+                    if rate_limited {
+                        break 'outer;
                     }
                 }
+            } else if self
+                .sync_unpackable_images(options, backend.as_ref(), group, &mut synced_assets)
+                .await
+            {
+                break 'outer;
+            }
+        }
+
+        // TODO: Clean up output of inputs that were present in the previous
+        // sync but are no longer present.
+    }
+
+    /// Syncs images that aren't packed into spritesheets. Images with the same
+    /// contents as one in `synced_assets` reuse its asset instead of being
+    /// uploaded again, and images that are uploaded are added to it. Returns
+    /// whether syncing was rate limited and should stop.
+    async fn sync_unpackable_images(
+        &mut self,
+        options: &SyncOptions,
+        backend: &dyn SyncBackend,
+        group: Vec<AssetName>,
+        synced_assets: &mut HashMap<String, SyncedAsset>,
+    ) -> bool {
+        let mut to_upload = Vec::new();
+        let mut duplicates = Vec::new();
+        let mut pending_hashes = HashSet::new();
+
+        // Checking this first means that unchanged inputs never need to be
+        // read or decoded.
+        for input_name in group {
+            if !self.needs_upload(&input_name) {
+                continue;
+            }
+
+            let hash = &self.inputs[&input_name].hash;
+
+            if synced_assets.contains_key(hash) || !pending_hashes.insert(hash.clone()) {
+                duplicates.push(input_name);
             } else {
-                for input_name in group {
-                    if let Err(err) = self
-                        .sync_unpackable_image(&options, &backend, &input_name)
-                        .await
-                    {
-                        if err.is::<SyncError>() {
-                            let err = err.downcast::<SyncError>().unwrap();
-                            let rate_limited = err.is_rate_limited();
-                            self.raise_error(err);
-                            if rate_limited {
-                                break 'outer;
-                            }
-                        } else {
-                            self.raise_error(err);
+                to_upload.push(input_name);
+            }
+        }
+
+        let mut rate_limited = false;
+
+        // Images are encoded in batches of one per thread, so that they're
+        // processed in parallel without every encoded image being held in
+        // memory at once.
+        let resize = options.resize;
+        let encoding = Progress::new("encode", Some(to_upload.len() as u64));
+        let uploading = Progress::new("upload", Some(to_upload.len() as u64));
+
+        'batches: for batch in to_upload.chunks(rayon::current_num_threads()) {
+            let inputs = &self.inputs;
+            let encoded_images: Vec<_> = batch
+                .par_iter()
+                .map(|input_name| {
+                    let encoded_image = encode_unpackable_image(&inputs[input_name], resize);
+                    encoding.inc(1);
+                    encoded_image
+                })
+                .collect();
+
+            for (input_name, encoded_image) in batch.iter().zip(encoded_images) {
+                let result = match encoded_image {
+                    Ok(encoded_image) => {
+                        self.sync_unpackable_image(options, backend, input_name, encoded_image)
+                            .await
+                    }
+                    Err(err) => Err(err.into()),
+                };
+                uploading.inc(1);
+
+                match result {
+                    Ok(()) => {
+                        self.input_results
+                            .insert(input_name.clone(), InputResult::Uploaded);
+
+                        let input = &self.inputs[input_name];
+
+                        if let Some(id) = &input.id {
+                            synced_assets.insert(
+                                input.hash.clone(),
+                                SyncedAsset {
+                                    id: id.clone(),
+                                    upload: input.upload.clone(),
+                                },
+                            );
+                        }
+                    }
+                    Err(err) => {
+                        self.record_failure(std::slice::from_ref(input_name), &err);
+
+                        rate_limited = Failure::of(&err) == Failure::RateLimited;
+                        self.raise_error(err);
+
+                        if rate_limited {
+                            break 'batches;
                         }
                     }
                 }
             }
         }
 
-        // TODO: Clean up output of inputs that were present in the previous
-        // sync but are no longer present.
+        drop(encoding);
+        drop(uploading);
+
+        for input_name in duplicates {
+            let input = self.inputs.get_mut(&input_name).unwrap();
+
+            // If the image these contents were going to be uploaded as failed,
+            // this input is left without an ID like it would have been.
+            let result = if let Some(asset) = synced_assets.get(&input.hash) {
+                log::info!(
+                    "{} has the same contents as an image that was already synced, reusing {}",
+                    input.human_name(),
+                    asset.id
+                );
+
+                input.id = Some(asset.id.clone());
+                input.upload.clone_from(&asset.upload);
+
+                InputResult::Reused
+            } else {
+                InputResult::Failed {
+                    error: "An image with the same contents could not be synced".to_owned(),
+                }
+            };
+
+            self.input_results.insert(input_name, result);
+        }
+
+        rate_limited
     }
 
     async fn sync_packable_images(
         &mut self,
         options: &SyncOptions,
         backend: &Box<dyn SyncBackend>,
-        group: Vec<AssetName>,
+        group: &[AssetName],
     ) -> Result<()> {
-        if self.are_inputs_unchanged(&group) {
+        if self.are_inputs_unchanged(group) {
             log::info!("Skipping image packing as all inputs are unchanged.");
 
             return Ok(());
         }
 
         log::trace!("Packing images...");
-        let mut packed_images = self.pack_images(&group)?;
-
-        log::trace!("Alpha-bleeding {} packed images...", packed_images.len());
+        let mut packed_images = self.pack_images(group)?;
 
-        for (i, packed_image) in packed_images.iter_mut().enumerate() {
-            log::trace!("Bleeding image {}", i);
+        log::trace!(
+            "Alpha-bleeding and encoding {} packed images...",
+            packed_images.len()
+        );
 
+        let bleeding = Progress::new("bleed", Some(packed_images.len() as u64));
+        packed_images.par_iter_mut().for_each(|packed_image| {
             alpha_bleed(&mut packed_image.img);
-        }
+            bleeding.inc(1);
+        });
+        drop(bleeding);
+
+        let encoding = Progress::new("encode", Some(packed_images.len() as u64));
+        let encoded_images: Vec<_> = packed_images
+            .par_iter()
+            .map(|packed_image| {
+                let encoded_image = encode_png(&packed_image.img);
+                encoding.inc(1);
+                encoded_image
+            })
+            .collect();
+        drop(encoding);
 
         log::trace!("Syncing packed images...");
-        for packed_image in &packed_images {
-            self.sync_packed_image(backend, packed_image).await?;
+        let uploading = Progress::new("upload", Some(packed_images.len() as u64));
+        for (packed_image, encoded_image) in packed_images.iter().zip(encoded_images) {
+            self.sync_packed_image(options, backend, packed_image, encoded_image)
+                .await?;
+            uploading.inc(1);
         }
 
         Ok(())
@@ -484,7 +1049,13 @@ impl SyncSession {
 
     fn are_inputs_unchanged(&self, group: &[AssetName]) -> bool {
         for name in group {
-            if let Some(manifest) = self.original_manifest.inputs.get(name) {
+            let previous = self
+                .original_manifest
+                .inputs
+                .get(name)
+                .and_then(|original| self.target.previous_state(original));
+
+            if let Some(previous) = previous {
                 let input = &self.inputs[name];
 
                 // If a sprite is local, it should be resynced just in case we decide to publish using roblox later...
@@ -492,7 +1063,7 @@ impl SyncSession {
                     return false;
                 }
 
-                let unchanged = input.is_unchanged_since_last_sync(manifest);
+                let unchanged = input.is_unchanged_since_last_sync(&previous);
 
                 if !unchanged {
                     log::trace!("Input {} changed since last sync", name);
@@ -516,10 +1087,19 @@ impl SyncSession {
         let mut packos_inputs = Vec::new();
         let mut images_by_id = HashMap::new();
 
-        for name in group {
-            let input = &self.inputs[name];
-            let img = image::load_from_memory(input.contents.as_slice())?;
+        let inputs = &self.inputs;
+        let packing = Progress::new("pack", Some(group.len() as u64));
+        let decoded_images: Vec<_> = group
+            .par_iter()
+            .map(|name| {
+                let img = decode_image(&inputs[name]);
+                packing.inc(1);
+                img
+            })
+            .collect();
 
+        for (name, img) in group.iter().zip(decoded_images) {
+            let img = img?;
             let input = InputItem::new(img.dimensions());
 
             images_by_id.insert(input.id(), (name, img));
@@ -561,23 +1141,15 @@ impl SyncSession {
 
     async fn sync_packed_image(
         &mut self,
+        options: &SyncOptions,
         backend: &Box<dyn SyncBackend>,
         packed_image: &PackedImage,
+        encoded_image: Vec<u8>,
     ) -> Result<()> {
-        let mut encoded_image: Vec<u8> = Vec::new();
-
         let (width, height) = packed_image.img.dimensions();
 
-        PngEncoder::new(&mut encoded_image)
-            .encode(
-                &packed_image.img.to_bytes(),
-                width,
-                height,
-                packed_image.img.color(),
-            )
-            .unwrap();
-
         let hash = generate_asset_hash(&encoded_image);
+        let size = encoded_image.len();
 
         let upload_data = UploadInfo {
             name: format!("spritesheet-{}", packed_image.index),
@@ -585,14 +1157,25 @@ impl SyncSession {
             hash,
         };
 
-        let id = backend.upload(upload_data).await?.id;
+        let response = backend.upload(upload_data).await?;
+        let upload = upload_record(
+            &self.profile,
+            options.target,
+            &response,
+            (width, height),
+            size,
+        );
 
         // Apply resolved metadata back to the inputs
         for (asset_name, slice) in &packed_image.slices {
             let input = self.inputs.get_mut(asset_name).unwrap();
 
-            input.id = Some(id.clone());
+            input.id = Some(response.id.clone());
             input.slice = Some(*slice);
+            input.upload.clone_from(&upload);
+
+            self.input_results
+                .insert(asset_name.clone(), InputResult::Uploaded);
         }
 
         Ok(())
@@ -601,68 +1184,77 @@ impl SyncSession {
     async fn sync_unpackable_image(
         &mut self,
         options: &SyncOptions,
-        backend: &Box<dyn SyncBackend>,
+        backend: &dyn SyncBackend,
         input_name: &AssetName,
+        encoded_image: EncodedImage,
     ) -> Result<()> {
         let config = self.root_config().clone();
         let input = self.inputs.get_mut(input_name).unwrap();
 
-        let mut img = match options.resize {
-            Some((width, height)) => {
-                let img = image::load_from_memory(&input.contents)?;
-                let img = resize(&img, width, height, image::imageops::FilterType::Gaussian);
-                DynamicImage::ImageRgba8(img)
-            }
-            None => image::load_from_memory(&input.contents)?,
-        };
-
-        alpha_bleed(&mut img);
-
-        let (width, height) = img.dimensions();
-
-        let mut encoded_image: Vec<u8> = Vec::new();
-        PngEncoder::new(&mut encoded_image)
-            .encode(&img.to_bytes(), width, height, img.color())
-            .unwrap();
-
         let uploaded_name = input.human_name();
         let uploaded_name = uploaded_name
             .strip_prefix(config.folder().to_str().unwrap())
             .unwrap();
+        let size = encoded_image.contents.len();
         let upload_data = UploadInfo {
             name: uploaded_name.to_string(),
-            contents: encoded_image.to_vec(),
+            contents: encoded_image.contents,
             hash: input.hash.clone(),
         };
 
-        let id = if let Some(input_manifest) = self.original_manifest.inputs.get(input_name) {
+        let response = backend.upload(upload_data).await?;
+
+        input.upload = upload_record(
+            &self.profile,
+            options.target,
+            &response,
+            encoded_image.dimensions,
+            size,
+        );
+        input.id = Some(response.id);
+
+        Ok(())
+    }
+
+    /// Tells whether an unpackable input needs to be uploaded, because it
+    /// changed or was never uploaded.
+    fn needs_upload(&self, input_name: &AssetName) -> bool {
+        let input = &self.inputs[input_name];
+        let previous = self
+            .original_manifest
+            .inputs
+            .get(input_name)
+            .and_then(|original| self.target.previous_state(original));
+
+        if let Some(previous) = previous {
             // This input existed during our last sync operation. We'll compare
             // the current state with the previous one to see if we need to take
             // action.
 
-            if input_manifest.hash != input.hash {
+            if previous.hash != input.hash {
                 // The file's contents have been edited since the last sync.
 
                 log::trace!("Contents changed...");
 
-                backend.upload(upload_data).await?.id
+                true
             } else if input.id.is_some() {
                 // The file's contents are the same as the previous sync and
                 // this image has been uploaded previously.
 
-                if input_manifest.packable != input.config.packable {
+                if previous.packable != input.config.packable {
                     // Only the file's config has changed.
                     //
                     // TODO: We might not need to reupload this image?
 
                     log::trace!("Config changed...");
 
-                    backend.upload(upload_data).await?.id
+                    true
                 } else {
                     // Nothing has changed, we're good to go!
 
                     log::trace!("Input is unchanged.");
-                    return Ok(());
+
+                    false
                 }
             } else {
                 // This image has never been uploaded, but its hash is present
@@ -670,22 +1262,27 @@ impl SyncSession {
 
                 log::trace!("Image has never been uploaded...");
 
-                backend.upload(upload_data).await?.id
+                true
             }
         } else {
             // This input was added since the last sync, if there was one.
 
             log::trace!("Image was added since last sync...");
 
-            backend.upload(upload_data).await?.id
-        };
+            true
+        }
+    }
+
+    fn write_manifest(&mut self, manifest: &Manifest) -> Result<()> {
+        let path = self.lock.manifest_path().to_owned();
 
-        input.id = Some(id);
+        manifest.write_to_file(&path)?;
+        self.files_written.push(path);
 
         Ok(())
     }
 
-    fn write_manifest(&self) -> Result<()> {
+    fn build_manifest(&self) -> Manifest {
         log::trace!("Generating new manifest");
 
         let mut manifest = Manifest::default();
@@ -694,41 +1291,86 @@ impl SyncSession {
             .inputs
             .iter()
             .map(|(name, input)| {
-                let id = input.id.as_ref().and_then(|asset_id| match asset_id {
-                    AssetId::Id(id) => Some(*id),
-                    _ => None,
-                });
-                (
-                    name.clone(),
-                    InputManifest {
+                let original = self.original_manifest.inputs.get(name);
+                let original_local = original.and_then(|original| original.local.clone());
+
+                let input_manifest = match (self.target, &input.id) {
+                    (SyncTarget::Local, id) => {
+                        let local = match id {
+                            Some(AssetId::Path(path)) => Some(LocalInputManifest {
+                                hash: input.hash.clone(),
+                                path: path.clone(),
+                                slice: input.slice,
+                                packable: input.config.packable,
+                            }),
+                            _ => original_local,
+                        };
+
+                        // Local syncs leave everything we know about uploads
+                        // as it was.
+                        match original {
+                            Some(original) => InputManifest {
+                                local,
+                                ..original.clone()
+                            },
+                            None => InputManifest {
+                                hash: input.hash.clone(),
+                                id: None,
+                                slice: None,
+                                packable: input.config.packable,
+                                upload: None,
+                                local,
+                            },
+                        }
+                    }
+                    (_, id) => InputManifest {
                         hash: input.hash.clone(),
-                        id,
+                        id: match id {
+                            Some(AssetId::Id(id)) => Some(*id),
+                            _ => None,
+                        },
                         slice: input.slice,
                         packable: input.config.packable,
+                        upload: input.upload.clone(),
+                        local: original_local,
                     },
-                )
+                };
+
+                (name.clone(), input_manifest)
             })
             .collect();
 
-        manifest.write_to_folder(self.root_config().folder())?;
+        // Nothing new is known about inputs that couldn't be read, so they're
+        // left as they were.
+        for name in self.unreadable_inputs.keys() {
+            if let Some(original) = self.original_manifest.inputs.get(name) {
+                manifest.inputs.insert(name.clone(), original.clone());
+            }
+        }
 
-        Ok(())
+        manifest
     }
 
-    fn codegen(&self) -> Result<(), SyncError> {
+    fn codegen(&mut self, affected: Option<&BTreeSet<AssetName>>) -> Result<(), SyncError> {
         log::trace!("Starting codegen");
 
         #[derive(Debug, Clone, PartialEq, Eq, Hash)]
         struct CodegenCompatibility<'a> {
             output_path: Option<&'a Path>,
+            format: CodegenFormat,
+            model_style: CodegenModelStyle,
+            dpi_mode: CodegenDpiMode,
         }
 
         let mut compatible_codegen_groups = HashMap::new();
 
         for (input_name, input) in &self.inputs {
-            let output_path = input.config.codegen_path.as_deref();
-
-            let compat = CodegenCompatibility { output_path };
+            let compat = CodegenCompatibility {
+                output_path: input.config.codegen_path.as_deref(),
+                format: input.config.codegen_format,
+                model_style: input.config.codegen_model_style,
+                dpi_mode: input.config.codegen_dpi_mode,
+            };
 
             let group = compatible_codegen_groups
                 .entry(compat)
@@ -736,19 +1378,68 @@ impl SyncSession {
             group.push(input_name.clone());
         }
 
-        for (compat, names) in compatible_codegen_groups {
+        let codegen_folder = self
+            .profile
+            .codegen_folder
+            .as_deref()
+            .map(|to| CodegenFolder {
+                from: self.root_config().folder(),
+                to,
+            });
+
+        let mut files_written = Vec::new();
+        let mut codegen_errors = Vec::new();
+        let generating = Progress::new("codegen", Some(compatible_codegen_groups.len() as u64));
+
+        for (compat, mut names) in compatible_codegen_groups {
+            generating.inc(1);
+
+            // Grouped files depend on every input in the group, but individual
+            // files only need to be generated again for affected inputs.
+            if let Some(affected) = affected {
+                if compat.output_path.is_some() {
+                    if !names.iter().any(|name| affected.contains(name)) {
+                        continue;
+                    }
+                } else {
+                    names.retain(|name| affected.contains(name));
+                }
+            }
+
             let inputs: Vec<_> = names.iter().map(|name| &self.inputs[name]).collect();
-            let output_path = compat.output_path;
+            let report = perform_codegen(
+                compat.output_path,
+                codegen_folder,
+                compat.format,
+                compat.model_style,
+                compat.dpi_mode,
+                &inputs,
+            )?;
+
+            files_written.extend(report.files_written);
+            codegen_errors.extend(report.errors);
+        }
+
+        drop(generating);
 
-            perform_codegen(output_path, &inputs)?;
+        for error in codegen_errors {
+            self.raise_error(error);
         }
 
+        if files_written.is_empty() {
+            log::debug!("Codegen did not change any files");
+        } else {
+            log::info!("Codegen updated {} file(s)", files_written.len());
+        }
+
+        self.files_written.extend(files_written);
+
         Ok(())
     }
 
-    fn write_asset_list(&self) -> Result<(), SyncError> {
+    fn write_asset_list(&mut self) -> Result<(), SyncError> {
         let list_path = match &self.root_config().asset_list_path {
-            Some(path) => path,
+            Some(path) => path.clone(),
             None => return Ok(()),
         };
 
@@ -757,7 +1448,7 @@ impl SyncSession {
         let list_parent = list_path.parent().unwrap();
         fs_err::create_dir_all(list_parent)?;
 
-        let mut file = BufWriter::new(fs_err::File::create(list_path)?);
+        let mut file = BufWriter::new(fs_err::File::create(&list_path)?);
 
         let known_ids: BTreeSet<&AssetId> = self
             .inputs
@@ -770,11 +1461,13 @@ impl SyncSession {
         }
 
         file.flush()?;
+        self.files_written.push(list_path);
+
         Ok(())
     }
 
     async fn populate_asset_cache(
-        &self,
+        &mut self,
         api_client: Box<dyn RobloxApiClient<'static> + Send + Sync>,
     ) -> Result<()> {
         let cache_path = match &self.root_config().asset_cache_path {
@@ -830,7 +1523,12 @@ impl SyncSession {
             }
         }
 
+        let mut downloaded = Vec::new();
+        let caching = Progress::new("cache", Some(self.inputs.len() as u64));
+
         for input in self.inputs.values() {
+            caching.inc(1);
+
             if let Some(id) = &input.id {
                 let input_path = cache_path.join(format!("{}", id));
 
@@ -850,16 +1548,364 @@ impl SyncSession {
                     log::debug!("Downloading asset ID {}", id);
 
                     let contents = api_client.download_image(*id).await?;
-                    fs_err::write(input_path, contents)?;
+                    fs_err::write(&input_path, contents)?;
+                    downloaded.push(input_path);
                 }
             }
         }
 
+        self.files_written.extend(downloaded);
+
         Ok(())
     }
 }
 
-fn is_image_asset(path: &Path) -> bool {
+/// Finds the inputs whose generated code could have changed since an earlier
+/// sync, or `None` if code for every input needs to be generated again because
+/// some inputs were removed.
+fn affected_inputs(
+    previous: &BTreeMap<AssetName, SyncInput>,
+    current: &BTreeMap<AssetName, SyncInput>,
+) -> Option<BTreeSet<AssetName>> {
+    if previous.keys().any(|name| !current.contains_key(name)) {
+        return None;
+    }
+
+    let affected = current
+        .iter()
+        .filter(|(name, input)| {
+            previous.get(*name).is_none_or(|previous| {
+                previous.hash != input.hash
+                    || previous.id != input.id
+                    || previous.slice != input.slice
+                    || previous.config != input.config
+            })
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    Some(affected)
+}
+
+/// Locate all of the configs connected to the given root config.
+///
+/// Runway config files can include each other via the `includes` field,
+/// which will search the given path for other config files and use them as
+/// part of the sync. The root config itself is not included in the result.
+pub(super) fn discover_included_configs(root_config: &Config) -> Result<Vec<Config>> {
+    // Each path to search is paired with the defaults of the config that
+    // included it, which any configs found there inherit.
+    let mut configs = Vec::new();
+    let mut to_search = VecDeque::new();
+    to_search.extend(included_paths(root_config));
+
+    while let Some((search_path, defaults)) = to_search.pop_front() {
+        let search_meta = fs::metadata(&search_path)?;
+
+        if search_meta.is_file() {
+            // This is a file that's explicitly named by a config. We'll
+            // check that it's a Tarmac config and include it.
+
+            let config = Config::read_included_file(&search_path, &defaults)?;
+
+            // Include any configs that this config references.
+            to_search.extend(included_paths(&config));
+
+            configs.push(config);
+        } else {
+            // If this directory contains a config file, we can stop
+            // traversing this branch.
+
+            match Config::read_included_folder(&search_path, &defaults) {
+                Ok(config) => {
+                    // We found a config, we're done here.
+
+                    // Append config include paths from this config
+                    to_search.extend(included_paths(&config));
+
+                    configs.push(config);
+                }
+
+                Err(err) if err.is_not_found() => {
+                    // We didn't find a config, keep searching down this
+                    // branch of the filesystem.
+
+                    let children = fs::read_dir(&search_path)?;
+
+                    for entry in children {
+                        let entry = entry?;
+                        let entry_path = entry.path();
+
+                        // DirEntry has a metadata method, but in the case
+                        // of symlinks, it returns metadata about the
+                        // symlink and not the file or folder.
+                        let entry_meta = fs::metadata(&entry_path)?;
+
+                        if entry_meta.is_dir() {
+                            to_search.push_back((entry_path, defaults.clone()));
+                        }
+                    }
+                }
+
+                Err(err) => {
+                    return Err(err.into());
+                }
+            }
+        }
+    }
+
+    Ok(configs)
+}
+
+fn included_paths(config: &Config) -> impl Iterator<Item = (PathBuf, InputDefaults)> + '_ {
+    config
+        .includes
+        .iter()
+        .map(move |include| (include.clone(), config.defaults.clone()))
+}
+
+/// The path of the manifest that a sync with the given profile uses.
+fn manifest_path(root_config: &Config, profile: &ResolvedProfile) -> PathBuf {
+    match &profile.manifest_path {
+        Some(path) => path.clone(),
+        None => Manifest::path_in_folder(root_config.folder()),
+    }
+}
+
+/// Reads the manifest that a sync with the given profile should start from.
+/// A missing manifest is treated like an empty one.
+pub(super) fn read_manifest(
+    root_config: &Config,
+    profile: &ResolvedProfile,
+) -> Result<Manifest, ManifestError> {
+    let manifest = match &profile.manifest_path {
+        Some(path) => Manifest::read_from_file(path),
+        None => Manifest::read_from_folder(root_config.folder()),
+    };
+
+    match manifest {
+        Err(err) if err.is_not_found() => Ok(Manifest::default()),
+        other => other,
+    }
+}
+
+/// The name of ignore files that exclude paths from being inputs, using the same
+/// syntax as `.gitignore` files, which are honored too.
+static IGNORE_FILENAME: &str = ".runwayignore";
+
+/// Finds all files matched by an input config's globs and not excluded by it
+/// or by an ignore file. Symlinks to files are always included, but symlinks
+/// to folders are only searched if the input config asks for symlinks to be
+/// followed.
+pub(super) fn find_input_paths<'a>(
+    config: &'a Config,
+    input_config: &'a InputConfig,
+) -> impl Iterator<Item = Result<PathBuf, ignore::Error>> + 'a {
+    let config_path = config.folder();
+    let base_path = config_path.join(input_config.glob.get_prefix());
+    log::trace!(
+        "Searching for inputs in '{}' matching '{}'",
+        base_path.display(),
+        input_config.glob,
+    );
+
+    let follow_symlinks = input_config.follow_symlinks;
+
+    WalkBuilder::new(base_path)
+        // Hidden files were always searched before ignore files were honored.
+        .hidden(false)
+        .require_git(false)
+        .add_custom_ignore_filename(IGNORE_FILENAME)
+        .follow_links(follow_symlinks)
+        .build()
+        .filter_map(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let match_path = entry.path().strip_prefix(config_path).ok()?;
+            let is_match = input_config.glob.is_match(match_path)
+                && !input_config.exclude.is_match(match_path);
+
+            if !is_match {
+                return None;
+            }
+
+            // Followed symlinks already have the file type of what they point
+            // to, but other symlinks need to be resolved to tell whether they
+            // point to a file. Symlinks that point nowhere are still found, so
+            // that they fail to be read and are reported as failed inputs.
+            let is_file = if entry.path_is_symlink() && !follow_symlinks {
+                match std::fs::metadata(entry.path()) {
+                    Ok(metadata) if metadata.is_dir() => {
+                        log::debug!("Not searching symlinked folder {}", entry.path().display());
+                        false
+                    }
+                    Ok(metadata) => metadata.is_file(),
+                    Err(_) => true,
+                }
+            } else {
+                entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_file())
+            };
+
+            is_file.then(|| Ok(entry.into_path()))
+        })
+}
+
+/// Names the input at the given path, failing if its path can't be used in a
+/// name or in generated code.
+pub(super) fn input_name(root_folder: &Path, path: &Path) -> Result<AssetName, SyncError> {
+    if path.to_str().is_none() {
+        return Err(SyncError::InvalidUnicode {
+            path: path.to_owned(),
+        });
+    }
+
+    AssetName::from_paths(root_folder, path).ok_or_else(|| SyncError::OutsideRootFolder {
+        path: path.to_owned(),
+        root_folder: root_folder.to_owned(),
+    })
+}
+
+/// An asset that an input was synced to, which inputs with the same contents
+/// can share.
+struct SyncedAsset {
+    id: AssetId,
+    upload: Option<UploadRecord>,
+}
+
+/// Finds the assets that unpackable images were synced to as of the last sync,
+/// keyed by the hash of their contents. Assets synced with another target or
+/// at another size can't be reused, so they're left out. When resizing, assets
+/// without an upload record are left out too, since their size isn't known.
+fn synced_assets_by_hash(
+    manifest: &Manifest,
+    target: SyncTarget,
+    resize: Option<(u32, u32)>,
+) -> HashMap<String, SyncedAsset> {
+    let mut assets = HashMap::new();
+
+    // The debug target numbers its assets from 1 again on every run, so an ID
+    // from an earlier run could belong to a different image now.
+    if matches!(target, SyncTarget::Debug) {
+        return assets;
+    }
+
+    for input_manifest in manifest.inputs.values() {
+        let Some(previous) = target.previous_state(input_manifest) else {
+            continue;
+        };
+
+        let Some(id) = previous.id else {
+            continue;
+        };
+
+        if previous.packable || previous.slice.is_some() {
+            continue;
+        }
+
+        let upload = match target {
+            SyncTarget::Local => None,
+            _ => input_manifest.upload.clone(),
+        };
+
+        let compatible = match &upload {
+            Some(upload) => {
+                Some(upload.target) == target.upload_target()
+                    && resize.is_none_or(|size| upload.dimensions == size)
+            }
+            None => resize.is_none(),
+        };
+
+        if compatible {
+            assets
+                .entry(previous.hash.to_owned())
+                .or_insert(SyncedAsset { id, upload });
+        }
+    }
+
+    assets
+}
+
+/// An image that's been prepared for upload, along with its dimensions.
+struct EncodedImage {
+    contents: Vec<u8>,
+    dimensions: (u32, u32),
+}
+
+fn decode_image(input: &SyncInput) -> Result<DynamicImage, SyncError> {
+    Ok(image::load_from_memory(&input.read_contents()?)?)
+}
+
+fn encode_png(img: &DynamicImage) -> Vec<u8> {
+    let (width, height) = img.dimensions();
+
+    let mut encoded_image: Vec<u8> = Vec::new();
+    PngEncoder::new(&mut encoded_image)
+        .encode(&img.to_bytes(), width, height, img.color())
+        .unwrap();
+
+    encoded_image
+}
+
+/// Decodes an unpackable input, then resizes, alpha-bleeds, and re-encodes it
+/// so that it's ready to be uploaded.
+fn encode_unpackable_image(
+    input: &SyncInput,
+    resize_to: Option<(u32, u32)>,
+) -> Result<EncodedImage, SyncError> {
+    let mut img = decode_image(input)?;
+
+    if let Some((width, height)) = resize_to {
+        let resized = resize(&img, width, height, imageops::FilterType::Gaussian);
+        img = DynamicImage::ImageRgba8(resized);
+    }
+
+    alpha_bleed(&mut img);
+
+    Ok(EncodedImage {
+        contents: encode_png(&img),
+        dimensions: img.dimensions(),
+    })
+}
+
+/// Reads the metadata and hash of an input. Its contents are only read if its
+/// hash isn't in the stat cache, in which case they're kept so that they don't
+/// need to be read again if the input is synced.
+///
+/// The metadata is `None` if the input changed while it was being read, since
+/// it might not describe the contents that were hashed. Inputs without
+/// metadata aren't added to the stat cache.
+fn read_input(
+    stat_cache: Option<&StatCache>,
+    path: &Path,
+) -> io::Result<(Option<Metadata>, Option<Vec<u8>>, String)> {
+    let metadata = fs::metadata(path)?;
+    let cached_hash = stat_cache.and_then(|cache| cache.get(path, &metadata));
+
+    if let Some(hash) = cached_hash {
+        return Ok((Some(metadata), None, hash.to_owned()));
+    }
+
+    let contents = fs::read(path)?;
+    let hash = generate_asset_hash(&contents);
+
+    let metadata_after = fs::metadata(path)?;
+    let unchanged = contents.len() as u64 == metadata.len()
+        && metadata_after.len() == metadata.len()
+        && metadata_after.modified().ok() == metadata.modified().ok();
+
+    if !unchanged {
+        log::debug!("{} changed while it was being read", path.display());
+    }
+
+    Ok((unchanged.then_some(metadata), Some(contents), hash))
+}
+
+pub(super) fn is_image_asset(path: &Path) -> bool {
     match path.extension().and_then(|ext| ext.to_str()) {
         // TODO: Expand the definition of images?
         Some("png") | Some("jpg") => true,
@@ -868,7 +1914,35 @@ fn is_image_asset(path: &Path) -> bool {
     }
 }
 
-fn generate_asset_hash(content: &[u8]) -> String {
+/// Describes an upload that just finished so that it can be recorded in the
+/// manifest.
+fn upload_record(
+    profile: &ResolvedProfile,
+    target: SyncTarget,
+    response: &UploadResponse,
+    dimensions: (u32, u32),
+    size: usize,
+) -> Option<UploadRecord> {
+    let target = target.upload_target()?;
+
+    // Creators only mean something for assets uploaded to Roblox.
+    let (creator_group_id, creator_user_id) = match target {
+        UploadTarget::Roblox => (profile.upload_to_group_id, profile.upload_to_user_id),
+        UploadTarget::Debug => (None, None),
+    };
+
+    Some(UploadRecord {
+        target,
+        backing_id: response.backing_id,
+        dimensions,
+        size: size as u64,
+        uploaded_at: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        creator_group_id,
+        creator_user_id,
+    })
+}
+
+pub(crate) fn generate_asset_hash(content: &[u8]) -> String {
     format!("{}", blake3::hash(content).to_hex())
 }
 
@@ -877,13 +1951,26 @@ pub enum SyncError {
     #[error("Path {} was described by more than one glob", .path.display())]
     OverlappingGlobs { path: PathBuf },
 
-    #[error("'tarmac sync' completed, but with {error_count} error(s)")]
-    HadErrors { error_count: usize },
+    #[error("'runway sync' completed, but with {error_count} error(s)")]
+    HadErrors {
+        error_count: usize,
+        failure: Failure,
+    },
+
+    #[error("Path {} is not valid Unicode, so it can't be used as an input", .path.display())]
+    InvalidUnicode { path: PathBuf },
+
+    #[error(
+        "Input {} is not inside {}, the folder containing the root config",
+        .path.display(),
+        .root_folder.display()
+    )]
+    OutsideRootFolder { path: PathBuf, root_folder: PathBuf },
 
     #[error(transparent)]
-    WalkDir {
+    Walk {
         #[from]
-        source: walkdir::Error,
+        source: ignore::Error,
     },
 
     #[error(transparent)]
@@ -892,12 +1979,24 @@ pub enum SyncError {
         source: ConfigError,
     },
 
+    #[error(transparent)]
+    Lock {
+        #[from]
+        source: SyncLockError,
+    },
+
     #[error(transparent)]
     Image {
         #[from]
         source: ImageError,
     },
 
+    #[error(transparent)]
+    Codegen {
+        #[from]
+        source: CodegenError,
+    },
+
     #[error(transparent)]
     Backend {
         #[from]
@@ -935,13 +2034,147 @@ pub enum SyncError {
     },
 }
 
-impl SyncError {
-    pub fn is_rate_limited(&self) -> bool {
-        match self {
-            Self::Backend {
-                source: SyncBackendError::RateLimited,
-            } => true,
-            _ => false,
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn upload(target: UploadTarget, dimensions: (u32, u32)) -> UploadRecord {
+        UploadRecord {
+            target,
+            backing_id: None,
+            dimensions,
+            size: 0,
+            uploaded_at: String::new(),
+            creator_group_id: None,
+            creator_user_id: None,
+        }
+    }
+
+    fn input_manifest(hash: &str, id: u64, upload: Option<UploadRecord>) -> InputManifest {
+        InputManifest {
+            hash: hash.to_owned(),
+            id: Some(id),
+            slice: None,
+            packable: false,
+            upload,
+            local: None,
+        }
+    }
+
+    fn manifest(inputs: Vec<(&str, InputManifest)>) -> Manifest {
+        let mut manifest = Manifest::default();
+        for (name, input) in inputs {
+            manifest.inputs.insert(AssetName::new(name), input);
         }
+        manifest
+    }
+
+    fn reused_id(assets: &HashMap<String, SyncedAsset>, hash: &str) -> Option<AssetId> {
+        assets.get(hash).map(|asset| asset.id.clone())
+    }
+
+    #[test]
+    fn dedup_by_hash() {
+        let mut packed = input_manifest("packed", 3, None);
+        packed.packable = true;
+
+        let manifest = manifest(vec![
+            ("a.png", input_manifest("same", 1, None)),
+            ("b.png", input_manifest("same", 2, None)),
+            ("c.png", packed),
+        ]);
+
+        let assets = synced_assets_by_hash(&manifest, SyncTarget::Roblox, None);
+
+        assert_eq!(reused_id(&assets, "same"), Some(AssetId::Id(1)));
+        assert_eq!(reused_id(&assets, "packed"), None);
+    }
+
+    #[test]
+    fn dedup_skips_other_targets() {
+        let manifest = manifest(vec![(
+            "a.png",
+            input_manifest("debug", 1, Some(upload(UploadTarget::Debug, (8, 8)))),
+        )]);
+
+        let assets = synced_assets_by_hash(&manifest, SyncTarget::Roblox, None);
+        assert_eq!(reused_id(&assets, "debug"), None);
+
+        // Debug IDs only mean something within the run that made them.
+        let assets = synced_assets_by_hash(&manifest, SyncTarget::Debug, None);
+        assert_eq!(reused_id(&assets, "debug"), None);
+    }
+
+    #[test]
+    fn dedup_with_resize() {
+        let manifest = manifest(vec![
+            (
+                "a.png",
+                input_manifest("resized", 1, Some(upload(UploadTarget::Roblox, (16, 16)))),
+            ),
+            (
+                "b.png",
+                input_manifest("other-size", 2, Some(upload(UploadTarget::Roblox, (8, 8)))),
+            ),
+            ("c.png", input_manifest("unrecorded", 3, None)),
+        ]);
+
+        let assets = synced_assets_by_hash(&manifest, SyncTarget::Roblox, Some((16, 16)));
+
+        assert_eq!(reused_id(&assets, "resized"), Some(AssetId::Id(1)));
+        assert_eq!(reused_id(&assets, "other-size"), None);
+        assert_eq!(reused_id(&assets, "unrecorded"), None);
+    }
+
+    // Nothing in /proc/self/mem is mapped at its start, so reading it fails
+    // even for users that can read any file, like root in CI containers.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn unreadable_input_keeps_manifest_entry() {
+        let folder = TempDir::new("sync-unreadable");
+
+        fs::write(
+            folder.join("runway.toml"),
+            "name = \"unreadable\"\n\n[[inputs]]\nglob = \"*.png\"\n",
+        )
+        .unwrap();
+        fs::write(folder.join("readable.png"), b"readable").unwrap();
+        std::os::unix::fs::symlink("/proc/self/mem", folder.join("unreadable.png")).unwrap();
+
+        let unreadable = input_manifest("unreadable", 2, None);
+        manifest(vec![
+            ("readable.png", input_manifest("readable", 1, None)),
+            ("unreadable.png", unreadable.clone()),
+        ])
+        .write_to_file(folder.join("runway-manifest.toml"))
+        .unwrap();
+
+        let mut session = SyncSession::new(folder.path(), None, SyncTarget::Roblox, false).unwrap();
+        session.discover_configs().unwrap();
+        session.discover_inputs().unwrap();
+
+        assert_eq!(session.sync_errors().len(), 1);
+        assert!(!session
+            .inputs()
+            .contains_key(&AssetName::new("unreadable.png")));
+
+        let report = session.report();
+        let failed = report
+            .inputs
+            .iter()
+            .find(|input| input.name.as_ref() == "unreadable.png")
+            .unwrap();
+        assert!(matches!(failed.result, InputResult::Failed { .. }));
+        assert!(failed.id.is_some());
+
+        let manifest = session.build_manifest();
+        assert_eq!(
+            manifest.inputs[&AssetName::new("unreadable.png")],
+            unreadable
+        );
+        assert!(manifest
+            .inputs
+            .contains_key(&AssetName::new("readable.png")));
     }
 }
@@ -15,6 +15,7 @@ use fs_err as fs;
 
 use image::{codecs::png::PngEncoder, imageops::resize, DynamicImage, GenericImageView};
 use log::{debug, info};
+use serde::Serialize;
 
 use std::{borrow::Cow, path::PathBuf};
 
@@ -35,7 +36,7 @@ pub struct UploadImageOptions {
     pub name: String,
 
     /// The description to give to the resulting Decal asset.
-    #[clap(long, default_value = "Uploaded by Tarmac.")]
+    #[clap(long, default_value = "Uploaded by Runway.")]
     pub description: String,
 
     /// The ID of the user to upload to. This option only has effect when using
@@ -62,6 +63,17 @@ pub struct UploadImageOptions {
     pub resize: Option<(u32, u32)>,
 }
 
+/// The result of uploading an image, printed when using JSON output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct UploadImageResult {
+    /// The ID of the Image asset to use in games.
+    asset_id: u64,
+
+    /// The ID of the Decal asset that the image was uploaded as.
+    backing_asset_id: u64,
+}
+
 fn parse_resize_var(env: &str) -> anyhow::Result<(u32, u32)> {
     if let Some((width, height)) = env
         .split_once('x')
@@ -74,6 +86,7 @@ fn parse_resize_var(env: &str) -> anyhow::Result<(u32, u32)> {
 }
 
 pub async fn upload_image(global: Global, options: UploadImageOptions) -> anyhow::Result<()> {
+    let output_format = global.output_format;
     let image_data = fs::read(options.path)?;
 
     let mut img = match options.resize {
@@ -120,5 +133,8 @@ pub async fn upload_image(global: Global, options: UploadImageOptions) -> anyhow
         response.backing_asset_id
     );
 
-    Ok(())
+    output_format.print_result(&UploadImageResult {
+        asset_id,
+        backing_asset_id: response.backing_asset_id,
+    })
 }
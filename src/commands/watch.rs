@@ -0,0 +1,245 @@
+/*
+ * Copyright (c) 2024 Paradoxum Games
+ * This file is licensed under the Mozilla Public License (MPL-2.0). A copy of it is available in the 'LICENSE' file at the root of the repository.
+*/
+
+use std::collections::BTreeSet;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Args;
+use notify_debouncer_full::{
+    new_debouncer,
+    notify::{EventKind, RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer, RecommendedCache,
+};
+use tokio::sync::mpsc;
+
+use crate::auth_cookie::get_auth_cookie;
+use crate::data::Config;
+use crate::options::{Global, OutputFormat};
+use crate::roblox_api::RobloxCredentials;
+
+use super::sync::{print_report, run_sync, SyncOptions, SyncSession};
+
+#[derive(Debug, Args)]
+pub struct WatchOptions {
+    #[clap(flatten)]
+    pub sync: SyncOptions,
+
+    /// How long to wait for files to stop changing before syncing them, in
+    /// milliseconds.
+    #[clap(long, default_value = "300")]
+    pub debounce: u64,
+}
+
+pub async fn watch(global: Global, options: WatchOptions) -> Result<()> {
+    let output_format = global.output_format;
+    let sync_options = &options.sync;
+    let fuzzy_config_path = match &sync_options.config_path {
+        Some(path) => path.clone(),
+        None => env::current_dir()?,
+    };
+    let profile_name = sync_options.profile.as_deref();
+    let token = global.auth.or_else(get_auth_cookie);
+
+    let mut session = None;
+    let first_sync = run_sync(
+        &mut session,
+        sync_options,
+        token.clone(),
+        global.api_key.clone(),
+    )
+    .await;
+
+    if let Err(err) = first_sync {
+        print_report(output_format, session.as_ref(), Some(&err))?;
+        return Err(err);
+    }
+
+    let mut session = session.expect("the session should be created by a successful sync");
+    let mut credentials = session.credentials(token.clone(), global.api_key.clone());
+    report_sync(output_format, &session, None)?;
+
+    // The debouncer calls back from its own thread, so changes are sent over a
+    // channel that can be awaited without blocking the runtime.
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let mut debouncer = new_debouncer(
+        Duration::from_millis(options.debounce),
+        None,
+        move |result: DebounceEventResult| {
+            let _ = sender.send(result);
+        },
+    )?;
+
+    let mut watched = watch_project(&mut debouncer, session.configs());
+
+    while let Some(result) = receiver.recv().await {
+        let events = match result {
+            Ok(events) => events,
+            Err(errors) => {
+                for err in errors {
+                    log::warn!("Error while watching for changes: {err}");
+                }
+                continue;
+            }
+        };
+
+        // Syncing reads every input, which would look like a change if
+        // accesses weren't left out.
+        let changed: BTreeSet<PathBuf> = events
+            .into_iter()
+            .filter(|event| !matches!(event.kind, EventKind::Access(_)))
+            .flat_map(|event| event.event.paths)
+            .collect();
+
+        for path in &changed {
+            log::trace!("{} changed", path.display());
+        }
+
+        let configs_changed = changed.iter().any(|path| is_config_path(&session, path));
+
+        if !configs_changed && !changed.iter().any(|path| is_input_path(&session, path)) {
+            continue;
+        }
+
+        log::info!("Files changed, syncing again...");
+
+        if configs_changed {
+            if let Err(err) = session.reload_configs(&fuzzy_config_path, profile_name) {
+                log::error!("Could not read the changed configs: {err:?}");
+                print_report(output_format, None, Some(&err))?;
+                continue;
+            }
+
+            credentials = session.credentials(token.clone(), global.api_key.clone());
+
+            for path in &watched {
+                let _ = debouncer.unwatch(path);
+            }
+            watched = watch_project(&mut debouncer, session.configs());
+        }
+
+        let result = resync(&mut session, sync_options, &credentials, configs_changed).await;
+        if let Err(err) = &result {
+            log::error!("{err:?}");
+        }
+
+        report_sync(output_format, &session, result.err().as_ref())?;
+    }
+
+    Ok(())
+}
+
+/// Discovers and syncs inputs again after files changed. Code is generated
+/// again for every input if configs changed, since any of their codegen options
+/// could have too.
+async fn resync(
+    session: &mut SyncSession,
+    options: &SyncOptions,
+    credentials: &RobloxCredentials,
+    configs_changed: bool,
+) -> Result<()> {
+    let previous_inputs = session.prepare_resync();
+    session.discover_inputs()?;
+
+    let previous_inputs = if configs_changed {
+        None
+    } else {
+        Some(&previous_inputs)
+    };
+
+    session.sync(options, credentials, previous_inputs).await
+}
+
+/// Reports that a sync finished, printing its report if results are printed
+/// as JSON, along with the error that stopped it early, if any.
+fn report_sync(
+    output_format: OutputFormat,
+    session: &SyncSession,
+    error: Option<&anyhow::Error>,
+) -> Result<()> {
+    let error_count = session.sync_errors().len();
+
+    if error_count > 0 {
+        log::error!("Sync completed with {error_count} error(s), watching for changes...");
+    } else {
+        log::info!("Sync completed, watching for changes...");
+    }
+
+    print_report(output_format, Some(session), error)
+}
+
+/// Watches the folders that inputs can be found in and the folders containing
+/// each config. Returns the watched paths so that they can be unwatched when
+/// the configs change.
+fn watch_project(
+    debouncer: &mut Debouncer<RecommendedWatcher, RecommendedCache>,
+    configs: &[Config],
+) -> Vec<PathBuf> {
+    let mut recursive = BTreeSet::new();
+
+    for config in configs {
+        for input_config in &config.inputs {
+            recursive.insert(config.folder().join(input_config.glob.get_prefix()));
+        }
+    }
+
+    // Config files are watched through their folder, because editors often
+    // save files by replacing them, which a watch on the file itself would
+    // lose track of.
+    let non_recursive: BTreeSet<_> = configs
+        .iter()
+        .map(|config| config.folder().to_owned())
+        .filter(|folder| !recursive.iter().any(|path| folder.starts_with(path)))
+        .collect();
+
+    let mut watched = Vec::new();
+
+    let paths = recursive
+        .into_iter()
+        .map(|path| (path, RecursiveMode::Recursive))
+        .chain(
+            non_recursive
+                .into_iter()
+                .map(|path| (path, RecursiveMode::NonRecursive)),
+        );
+
+    for (path, mode) in paths {
+        match debouncer.watch(&path, mode) {
+            Ok(()) => {
+                log::debug!("Watching {}", path.display());
+                watched.push(path);
+            }
+            Err(err) => log::warn!("Could not watch {}: {}", path.display(), err),
+        }
+    }
+
+    watched
+}
+
+fn is_config_path(session: &SyncSession, path: &Path) -> bool {
+    session
+        .configs()
+        .iter()
+        .any(|config| config.file_path == path)
+}
+
+/// Tells whether a changed path is, or could now be, one of the session's
+/// inputs.
+fn is_input_path(session: &SyncSession, path: &Path) -> bool {
+    let is_known_input = session.inputs().values().any(|input| input.path == path);
+
+    is_known_input
+        || session.configs().iter().any(|config| {
+            let Ok(match_path) = path.strip_prefix(config.folder()) else {
+                return false;
+            };
+
+            config.inputs.iter().any(|input_config| {
+                input_config.glob.is_match(match_path) && !input_config.exclude.is_match(match_path)
+            })
+        })
+}
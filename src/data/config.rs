@@ -1,21 +1,40 @@
 use std::{
+    collections::BTreeMap,
     io,
     path::{Path, PathBuf},
 };
 
 use fs_err as fs;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use toml::{value::Table, Value};
 
-use crate::glob::Glob;
+use crate::{data::Manifest, glob::GlobList};
 
-static CONFIG_FILENAME: &str = "tarmac.toml";
+pub static CONFIG_FILENAME: &str = "runway.toml";
 
-/// Configuration for Tarmac, contained in a tarmac.toml file.
+/// The name that Tarmac gave config files. Projects that haven't been migrated
+/// with `runway migrate` still use it.
+pub static LEGACY_CONFIG_FILENAME: &str = "tarmac.toml";
+
+/// Keys that only have an effect in the root config. Included configs that set
+/// them get a warning, since they would otherwise be ignored without a trace.
+static ROOT_ONLY_KEYS: &[&str] = &[
+    "max-spritesheet-size",
+    "spritesheet-padding-size",
+    "asset-cache-path",
+    "asset-list-path",
+    "upload-to-group-id",
+    "upload-to-user-id",
+    "upload-cache",
+];
+
+/// Configuration for Runway, contained in a runway.toml file.
 ///
-/// Tarmac is started from a top-level tarmac.toml file. Config files can
+/// Runway is started from a top-level runway.toml file. Config files can
 /// include other config files.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct Config {
     /// The name of the project, currently only used in debugging.
@@ -35,7 +54,7 @@ pub struct Config {
     /// stored. Each asset's name will match its asset ID.
     pub asset_cache_path: Option<PathBuf>,
 
-    /// A path to a file where Tarmac will write a list of all of the asset URLs
+    /// A path to a file where Runway will write a list of all of the asset URLs
     /// referred to by this project.
     pub asset_list_path: Option<PathBuf>,
 
@@ -48,14 +67,34 @@ pub struct Config {
     /// Cloud API.
     pub upload_to_user_id: Option<u64>,
 
-    /// A list of paths that Tarmac should search in to find other Tarmac
+    /// A cache of uploaded images shared by everyone working on the project,
+    /// so that images someone already uploaded aren't uploaded again. Either
+    /// the URL of an HTTP service, or a path to a folder, like one on a network
+    /// share. Only applies if this config is the root config file.
+    pub upload_cache: Option<String>,
+
+    /// Named sets of upload options that can be picked with the `--profile`
+    /// flag, like separate groups for development and production. Only applies
+    /// if this config is the root config file.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+
+    /// Options that apply to every input in this config and in the configs it
+    /// includes, unless the input sets them itself. Defaults in included
+    /// configs take priority over the ones they inherit.
+    // Once a config has been read, this also contains the defaults it
+    // inherited.
+    #[serde(default)]
+    pub defaults: InputDefaults,
+
+    /// A list of paths that Runway should search in to find other Runway
     /// projects.
     ///
     /// Any found projects will have their inputs merged into this project.
     #[serde(default)]
     pub includes: Vec<PathBuf>,
 
-    /// A list of input glob paths and options that Tarmac should use to
+    /// A list of input glob paths and options that Runway should use to
     /// discover assets that it should manage.
     #[serde(default)]
     pub inputs: Vec<InputConfig>,
@@ -79,26 +118,193 @@ impl Config {
     }
 
     pub fn read_from_folder<P: AsRef<Path>>(folder_path: P) -> Result<Self, ConfigError> {
-        let folder_path = folder_path.as_ref();
-        let file_path = &folder_path.join(CONFIG_FILENAME);
+        Self::read_from_file(Self::path_in_folder(folder_path.as_ref()))
+    }
 
-        Self::read_from_file(file_path)
+    /// The path of the config file in the given folder. This is `runway.toml`,
+    /// unless the folder only has a `tarmac.toml`.
+    pub fn path_in_folder(folder_path: &Path) -> PathBuf {
+        let file_path = folder_path.join(CONFIG_FILENAME);
+        let legacy_file_path = folder_path.join(LEGACY_CONFIG_FILENAME);
+
+        if !file_path.exists() && legacy_file_path.exists() {
+            legacy_file_path
+        } else {
+            file_path
+        }
     }
 
     pub fn read_from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
-        let path = path.as_ref();
-        let contents = fs::read(path)?;
+        Self::read(path.as_ref(), None)
+    }
 
-        let mut config: Self = toml::from_slice(&contents).map_err(|source| ConfigError::Toml {
+    /// Reads a config that was included by another config, inheriting the
+    /// defaults of the config that included it.
+    pub fn read_included_file<P: AsRef<Path>>(
+        path: P,
+        inherited: &InputDefaults,
+    ) -> Result<Self, ConfigError> {
+        Self::read(path.as_ref(), Some(inherited))
+    }
+
+    /// Like `read_included_file`, but finds the config in the given folder.
+    pub fn read_included_folder<P: AsRef<Path>>(
+        folder_path: P,
+        inherited: &InputDefaults,
+    ) -> Result<Self, ConfigError> {
+        Self::read(&Self::path_in_folder(folder_path.as_ref()), Some(inherited))
+    }
+
+    fn read(path: &Path, inherited: Option<&InputDefaults>) -> Result<Self, ConfigError> {
+        let contents = fs::read(path)?;
+        let toml_error = |source| ConfigError::Toml {
             source,
             path: path.to_owned(),
-        })?;
+        };
+
+        let mut config: Self = toml::from_slice(&contents).map_err(toml_error)?;
         config.file_path = path.to_owned();
+
+        // Included configs can't be synced on their own, so a profile declared
+        // in one could never be selected.
+        if inherited.is_some() && !config.profiles.is_empty() {
+            return Err(ConfigError::IncludedProfiles {
+                path: path.to_owned(),
+            });
+        }
+
+        if let Some(inherited) = inherited {
+            config.defaults.inherit(inherited);
+        }
+
+        if inherited.is_some() || config.defaults != InputDefaults::default() {
+            let table: Table = toml::from_slice(&contents).map_err(toml_error)?;
+
+            if inherited.is_some() {
+                config.warn_about_root_only_keys(&table);
+            }
+
+            config.apply_defaults(table)?;
+        }
+
         config.make_paths_absolute();
+        config.validate_inputs()?;
 
         Ok(config)
     }
 
+    /// Rejects inputs that combine options which can't be generated together.
+    fn validate_inputs(&self) -> Result<(), ConfigError> {
+        for (index, input) in self.inputs.iter().enumerate() {
+            // Only `module-script` models contain Lua source that the resolver
+            // helper can be generated into.
+            let uses_instances = input.codegen_format != CodegenFormat::Lua
+                && input.codegen_model_style != CodegenModelStyle::ModuleScript;
+
+            if input.codegen_dpi_mode == CodegenDpiMode::Resolver && uses_instances {
+                return Err(ConfigError::UnsupportedDpiMode {
+                    input: index + 1,
+                    path: self.file_path.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn warn_about_root_only_keys(&self, table: &Table) {
+        for key in root_only_keys(table) {
+            log::warn!(
+                "{} sets '{}', which only has an effect in the root config",
+                self.file_path.display(),
+                key
+            );
+        }
+    }
+
+    /// Reads this config's inputs again from the given table, which is the
+    /// contents of the config file, filling in any options that the inputs
+    /// don't set from this config's defaults.
+    fn apply_defaults(&mut self, mut table: Table) -> Result<(), ConfigError> {
+        let toml_error = |source| ConfigError::Toml {
+            source,
+            path: self.file_path.clone(),
+        };
+
+        let Some(Value::Array(mut inputs)) = table.remove("inputs") else {
+            return Ok(());
+        };
+
+        let defaults = match Value::try_from(&self.defaults) {
+            Ok(Value::Table(defaults)) => defaults,
+            Ok(_) => unreachable!("InputDefaults always serializes to a table"),
+            Err(source) => {
+                return Err(ConfigError::Defaults {
+                    source,
+                    path: self.file_path.clone(),
+                })
+            }
+        };
+
+        for input in &mut inputs {
+            if let Value::Table(input) = input {
+                for (key, value) in &defaults {
+                    if !input.contains_key(key) {
+                        input.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+
+        self.inputs = Value::Array(inputs).try_into().map_err(toml_error)?;
+
+        Ok(())
+    }
+
+    /// Resolves the upload options that a sync should use, taking them from
+    /// the profile with the given name if there is one.
+    pub fn resolve_profile(&self, name: Option<&str>) -> Result<ResolvedProfile, ConfigError> {
+        let Some(name) = name else {
+            return Ok(ResolvedProfile {
+                name: None,
+                upload_to_group_id: self.upload_to_group_id,
+                upload_to_user_id: self.upload_to_user_id,
+                manifest_path: None,
+                codegen_folder: None,
+            });
+        };
+
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| ConfigError::UnknownProfile {
+                name: name.to_owned(),
+                path: self.file_path.clone(),
+            })?;
+
+        // A profile that doesn't name a creator uploads to the same place as
+        // the rest of the config.
+        let (upload_to_group_id, upload_to_user_id) =
+            if profile.upload_to_group_id.is_some() || profile.upload_to_user_id.is_some() {
+                (profile.upload_to_group_id, profile.upload_to_user_id)
+            } else {
+                (self.upload_to_group_id, self.upload_to_user_id)
+            };
+
+        let manifest_path = profile
+            .manifest_path
+            .clone()
+            .unwrap_or_else(|| Manifest::profile_path(self.folder(), name));
+
+        Ok(ResolvedProfile {
+            name: Some(name.to_owned()),
+            upload_to_group_id,
+            upload_to_user_id,
+            manifest_path: Some(manifest_path),
+            codegen_folder: profile.codegen_folder.clone(),
+        })
+    }
+
     /// The path that paths in this Config should be considered relative to.
     pub fn folder(&self) -> &Path {
         self.file_path.parent().unwrap()
@@ -116,10 +322,28 @@ impl Config {
             make_absolute(cache_path, base);
         }
 
+        for profile in self.profiles.values_mut() {
+            if let Some(manifest_path) = profile.manifest_path.as_mut() {
+                make_absolute(manifest_path, base);
+            }
+
+            if let Some(codegen_folder) = profile.codegen_folder.as_mut() {
+                make_absolute(codegen_folder, base);
+            }
+        }
+
         for include in &mut self.includes {
             make_absolute(include, base);
         }
 
+        if let Some(codegen_path) = self.defaults.codegen_path.as_mut() {
+            make_absolute(codegen_path, base);
+        }
+
+        if let Some(codegen_base_path) = self.defaults.codegen_base_path.as_mut() {
+            make_absolute(codegen_base_path, base);
+        }
+
         for input in &mut self.inputs {
             if let Some(codegen_path) = input.codegen_path.as_mut() {
                 make_absolute(codegen_path, base);
@@ -130,6 +354,15 @@ impl Config {
     }
 }
 
+/// Finds the keys in a config file's contents that only have an effect in the
+/// root config.
+fn root_only_keys(table: &Table) -> impl Iterator<Item = &'static str> + '_ {
+    ROOT_ONLY_KEYS
+        .iter()
+        .copied()
+        .filter(move |key| table.contains_key(*key))
+}
+
 fn default_max_spritesheet_size() -> (u32, u32) {
     (1024, 1024)
 }
@@ -137,14 +370,118 @@ fn default_spritesheet_padding_size() -> u32 {
     1
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Options for inputs, declared in a `[defaults]` table, that apply to every
+/// input that doesn't set them itself. Each option has the same meaning as the
+/// `InputConfig` option with the same name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct InputDefaults {
+    pub codegen: Option<bool>,
+    pub codegen_path: Option<PathBuf>,
+    pub codegen_base_path: Option<PathBuf>,
+    pub codegen_format: Option<CodegenFormat>,
+    pub codegen_model_style: Option<CodegenModelStyle>,
+    pub codegen_dpi_mode: Option<CodegenDpiMode>,
+    pub codegen_key_style: Option<CodegenKeyStyle>,
+    pub codegen_strip_prefix: Option<String>,
+    pub codegen_strip_suffix: Option<String>,
+    pub packable: Option<bool>,
+    pub follow_symlinks: Option<bool>,
+}
+
+impl InputDefaults {
+    /// Fills in any options that aren't set here from the defaults of a config
+    /// that includes this one.
+    fn inherit(&mut self, parent: &InputDefaults) {
+        fn inherit<T: Clone>(option: &mut Option<T>, parent: Option<&T>) {
+            if option.is_none() {
+                *option = parent.cloned();
+            }
+        }
+
+        inherit(&mut self.codegen, parent.codegen.as_ref());
+        inherit(&mut self.codegen_path, parent.codegen_path.as_ref());
+        inherit(
+            &mut self.codegen_base_path,
+            parent.codegen_base_path.as_ref(),
+        );
+        inherit(&mut self.codegen_format, parent.codegen_format.as_ref());
+        inherit(
+            &mut self.codegen_model_style,
+            parent.codegen_model_style.as_ref(),
+        );
+        inherit(&mut self.codegen_dpi_mode, parent.codegen_dpi_mode.as_ref());
+        inherit(
+            &mut self.codegen_key_style,
+            parent.codegen_key_style.as_ref(),
+        );
+        inherit(
+            &mut self.codegen_strip_prefix,
+            parent.codegen_strip_prefix.as_ref(),
+        );
+        inherit(
+            &mut self.codegen_strip_suffix,
+            parent.codegen_strip_suffix.as_ref(),
+        );
+        inherit(&mut self.packable, parent.packable.as_ref());
+        inherit(&mut self.follow_symlinks, parent.follow_symlinks.as_ref());
+    }
+}
+
+/// A named set of upload options, declared in a `[profiles.<name>]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ProfileConfig {
+    /// The group to upload to when this profile is active. If neither this
+    /// nor `upload-to-user-id` are given, the config's own options are used.
+    pub upload_to_group_id: Option<u64>,
+
+    /// The user to upload to when this profile is active. This field only has
+    /// effect when using the Open Cloud API.
+    pub upload_to_user_id: Option<u64>,
+
+    /// Where to store the manifest for this profile, so that each profile
+    /// keeps track of its own asset IDs. Defaults to
+    /// `runway-manifest.<name>.toml` next to the config file.
+    pub manifest_path: Option<PathBuf>,
+
+    /// A folder to write generated code to when this profile is active, so
+    /// that code for each profile refers to the asset IDs uploaded with it.
+    /// Files keep the path they would have relative to the config's folder.
+    /// If not given, generated code is written to the usual place.
+    pub codegen_folder: Option<PathBuf>,
+}
+
+/// The upload options that a sync ends up using after applying the selected
+/// profile, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedProfile {
+    pub name: Option<String>,
+    pub upload_to_group_id: Option<u64>,
+    pub upload_to_user_id: Option<u64>,
+
+    /// The manifest to read and write, or `None` to use the default manifest
+    /// next to the config file.
+    pub manifest_path: Option<PathBuf>,
+
+    /// The folder to write generated code to instead of the usual place, if
+    /// any.
+    pub codegen_folder: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct InputConfig {
-    /// A glob that will match all files that should be considered for this
-    /// group of inputs.
-    pub glob: Glob,
+    /// A glob, or a list of globs, that will match all files that should be
+    /// considered for this group of inputs.
+    pub glob: GlobList,
+
+    /// Globs matching files that should be left out of this group of inputs,
+    /// even if `glob` matches them.
+    #[serde(default)]
+    pub exclude: GlobList,
 
-    /// Defines whether Tarmac should generate code to import the assets
+    /// Defines whether Runway should generate code to import the assets
     /// associated with this group of inputs.
     #[serde(default)]
     pub codegen: bool,
@@ -154,9 +491,41 @@ pub struct InputConfig {
     #[serde(default)]
     pub codegen_path: Option<PathBuf>,
 
+    /// The path that grouped codegen keys are relative to. Defaults to the
+    /// folder containing the config file.
     #[serde(default)]
     pub codegen_base_path: PathBuf,
 
+    /// The kind of file that Runway should generate for this group of inputs.
+    /// Model formats can be inserted directly into Roblox Studio, which is
+    /// useful for places that aren't managed by a tool like Rojo.
+    #[serde(default)]
+    pub codegen_format: CodegenFormat,
+
+    /// Which instances Runway should create when `codegen-format` is a model
+    /// format. Has no effect when generating Lua.
+    #[serde(default)]
+    pub codegen_model_style: CodegenModelStyle,
+
+    /// How grouped codegen should pick between high DPI variants of an asset.
+    #[serde(default)]
+    pub codegen_dpi_mode: CodegenDpiMode,
+
+    /// How file and folder names should be transformed into keys in grouped
+    /// codegen.
+    #[serde(default)]
+    pub codegen_key_style: CodegenKeyStyle,
+
+    /// A prefix to strip from file names before turning them into keys in
+    /// grouped codegen, like `ic-`.
+    #[serde(default)]
+    pub codegen_strip_prefix: Option<String>,
+
+    /// A suffix to strip from file names before turning them into keys in
+    /// grouped codegen, like `_icon`.
+    #[serde(default)]
+    pub codegen_strip_suffix: Option<String>,
+
     /// Whether the assets affected by this config are allowed to be packed into
     /// spritesheets.
     ///
@@ -166,6 +535,87 @@ pub struct InputConfig {
     /// instances.
     #[serde(default)]
     pub packable: bool,
+
+    /// Whether symlinks to folders should be followed when searching for files
+    /// matching this config's globs. Symlinks to files are always included.
+    #[serde(default)]
+    pub follow_symlinks: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodegenFormat {
+    /// Plain Lua source files, intended to be turned into `ModuleScript`
+    /// instances by a tool like Rojo.
+    #[default]
+    Lua,
+
+    /// XML Roblox model files.
+    Rbxmx,
+
+    /// Binary Roblox model files.
+    Rbxm,
+}
+
+impl CodegenFormat {
+    /// The file extension that files generated in this format should have.
+    pub fn extension(self) -> &'static str {
+        match self {
+            CodegenFormat::Lua => "lua",
+            CodegenFormat::Rbxmx => "rbxmx",
+            CodegenFormat::Rbxm => "rbxm",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodegenModelStyle {
+    /// A single `ModuleScript` containing the same source that Lua codegen
+    /// would produce.
+    #[default]
+    ModuleScript,
+
+    /// A tree of `Folder` instances mirroring the structure of the inputs,
+    /// with one `ModuleScript` per asset.
+    ModuleTree,
+
+    /// A tree of `Folder` instances mirroring the structure of the inputs,
+    /// with one `StringValue` per asset. Spritesheet slices are stored in the
+    /// `ImageRectOffset` and `ImageRectSize` attributes, and high DPI variants
+    /// are stored as children named after their scale, like `@2x`.
+    Values,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodegenDpiMode {
+    /// Each asset with high DPI variants is generated as a function that takes
+    /// a DPI scale and returns the best variant.
+    #[default]
+    Function,
+
+    /// Assets are stored in a flat table keyed by path, and the generated
+    /// module only exposes a `get(path, dpiScale)` function that picks the
+    /// best variant of any asset.
+    Resolver,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodegenKeyStyle {
+    /// Use file and folder names as they are.
+    #[default]
+    Preserve,
+
+    /// `ic-close_button` becomes `IcCloseButton`.
+    PascalCase,
+
+    /// `ic-close_button` becomes `icCloseButton`.
+    CamelCase,
+
+    /// `ic-close_button` becomes `ic_close_button`.
+    SnakeCase,
 }
 
 #[derive(Debug, Error)]
@@ -176,6 +626,28 @@ pub enum ConfigError {
         source: toml::de::Error,
     },
 
+    #[error("Error applying the defaults in {}", .path.display())]
+    Defaults {
+        path: PathBuf,
+        source: toml::ser::Error,
+    },
+
+    #[error(
+        "Input {input} in {} sets codegen-dpi-mode = \"resolver\", which only works \
+         with Lua codegen or the 'module-script' model style",
+        .path.display()
+    )]
+    UnsupportedDpiMode { input: usize, path: PathBuf },
+
+    #[error(
+        "{} declares profiles, but profiles can only be declared in the root config",
+        .path.display()
+    )]
+    IncludedProfiles { path: PathBuf },
+
+    #[error("Profile '{name}' is not defined in {}", .path.display())]
+    UnknownProfile { name: String, path: PathBuf },
+
     #[error(transparent)]
     Io {
         #[from]
@@ -204,3 +676,172 @@ fn make_absolute(path: &mut PathBuf, base: &Path) {
         *path = new_path;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// Writes a config with the given contents to the given folder, returning
+    /// the path of the config.
+    fn write_config(folder: &Path, contents: &str) -> PathBuf {
+        fs::create_dir_all(folder).unwrap();
+
+        let path = folder.join(CONFIG_FILENAME);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn resolver_with_instance_model_style() {
+        let folder = TempDir::new("resolver-values");
+        let path = write_config(
+            folder.path(),
+            r#"
+                name = "resolver-values"
+
+                [[inputs]]
+                glob = "**/*.png"
+                codegen = true
+                codegen-path = "Assets.rbxm"
+                codegen-format = "rbxm"
+                codegen-model-style = "values"
+                codegen-dpi-mode = "resolver"
+            "#,
+        );
+
+        assert!(matches!(
+            Config::read_from_file(&path),
+            Err(ConfigError::UnsupportedDpiMode { input: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn resolver_with_module_script_model_style() {
+        let folder = TempDir::new("resolver-module-script");
+        let path = write_config(
+            folder.path(),
+            r#"
+                name = "resolver-module-script"
+
+                [[inputs]]
+                glob = "**/*.png"
+                codegen = true
+                codegen-path = "Assets.rbxmx"
+                codegen-format = "rbxmx"
+                codegen-dpi-mode = "resolver"
+            "#,
+        );
+
+        Config::read_from_file(&path).unwrap();
+    }
+
+    #[test]
+    fn included_profiles() {
+        let folder = TempDir::new("included-profiles");
+        let path = write_config(
+            folder.path(),
+            r#"
+                name = "included-profiles"
+
+                [profiles.dev]
+                upload-to-group-id = 1234
+            "#,
+        );
+
+        Config::read_from_file(&path).unwrap();
+        assert!(matches!(
+            Config::read_included_file(&path, &InputDefaults::default()),
+            Err(ConfigError::IncludedProfiles { .. })
+        ));
+    }
+
+    #[test]
+    fn profile_codegen_folder() {
+        let folder = TempDir::new("profile-codegen-folder");
+        let path = write_config(
+            folder.path(),
+            r#"
+                name = "profile-codegen-folder"
+
+                [profiles.dev]
+                codegen-folder = "generated/dev"
+
+                [profiles.production]
+            "#,
+        );
+
+        let config = Config::read_from_file(&path).unwrap();
+
+        let dev = config.resolve_profile(Some("dev")).unwrap();
+        assert_eq!(
+            dev.codegen_folder,
+            Some(config.folder().join("generated/dev"))
+        );
+
+        let production = config.resolve_profile(Some("production")).unwrap();
+        assert_eq!(production.codegen_folder, None);
+    }
+
+    #[test]
+    fn input_defaults_cascade() {
+        let folder = TempDir::new("defaults-cascade");
+        let root_path = write_config(
+            folder.path(),
+            r#"
+                name = "defaults-cascade"
+
+                [defaults]
+                packable = true
+                codegen-key-style = "pascal-case"
+                codegen-strip-prefix = "ic-"
+            "#,
+        );
+        let root = Config::read_from_file(&root_path).unwrap();
+
+        let included_path = write_config(
+            &folder.join("included"),
+            r#"
+                name = "included"
+
+                [defaults]
+                codegen-strip-prefix = "icon-"
+
+                [[inputs]]
+                glob = "**/*.png"
+                packable = false
+            "#,
+        );
+        let included = Config::read_included_file(&included_path, &root.defaults).unwrap();
+        let input = &included.inputs[0];
+
+        // Set by the input itself.
+        assert!(!input.packable);
+
+        // Set by the included config's defaults, over the root config's.
+        assert_eq!(input.codegen_strip_prefix.as_deref(), Some("icon-"));
+
+        // Left unset, so inherited from the root config.
+        assert_eq!(input.codegen_key_style, CodegenKeyStyle::PascalCase);
+    }
+
+    #[test]
+    fn root_only_keys_in_included_config() {
+        let table: Table = toml::from_str(
+            r#"
+                name = "included"
+                upload-to-group-id = 1234
+                asset-list-path = "assets.txt"
+
+                [defaults]
+                packable = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            root_only_keys(&table).collect::<Vec<_>>(),
+            ["asset-list-path", "upload-to-group-id"]
+        );
+    }
+}
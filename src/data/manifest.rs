@@ -1,5 +1,6 @@
 use std::{
     collections::BTreeMap,
+    convert::TryFrom,
     io,
     path::{Path, PathBuf},
 };
@@ -7,36 +8,134 @@ use std::{
 use fs_err as fs;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use toml::{value::Table, Value};
 
-use crate::asset_name::AssetName;
+use crate::{asset_name::AssetName, data::AssetId};
 
-static MANIFEST_FILENAME: &str = "tarmac-manifest.toml";
+static MANIFEST_FILENAME: &str = "runway-manifest.toml";
+
+/// The name that Tarmac gave manifest files. Projects that haven't been
+/// migrated with `runway migrate` still use it.
+pub static LEGACY_MANIFEST_FILENAME: &str = "tarmac-manifest.toml";
+
+/// The version of the manifest format that this version of Runway reads and
+/// writes. Bump this and add an entry to `MIGRATIONS` whenever the format
+/// changes in a way that older manifests can't be read as-is.
+pub const MANIFEST_VERSION: u32 = 2;
+
+/// Upgrades manifests from one version to the next. The migration at index `n`
+/// upgrades a manifest from version `n` to version `n + 1`.
+static MIGRATIONS: &[fn(&mut Table)] = &[migrate_v0_to_v1, migrate_v1_to_v2];
 
 /// Tracks the status of all configuration, inputs, and outputs as of the last
 /// sync operation.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Manifest {
+    /// The version of the manifest format. Manifests written before this field
+    /// existed, including ones written by the original Tarmac, are version 0.
+    pub version: u32,
+
     pub inputs: BTreeMap<AssetName, InputManifest>,
 }
 
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            inputs: BTreeMap::new(),
+        }
+    }
+}
+
 impl Manifest {
     pub fn read_from_folder<P: AsRef<Path>>(folder_path: P) -> Result<Self, ManifestError> {
-        let folder_path = folder_path.as_ref();
-        let file_path = &folder_path.join(MANIFEST_FILENAME);
+        Self::read_from_file(Self::path_in_folder(folder_path.as_ref()))
+    }
+
+    /// The path of the manifest in the given folder. This is
+    /// `runway-manifest.toml`, unless the folder only has a
+    /// `tarmac-manifest.toml`, so that existing projects keep updating the
+    /// manifest they already have.
+    pub fn path_in_folder(folder_path: &Path) -> PathBuf {
+        prefer_existing(
+            folder_path.join(MANIFEST_FILENAME),
+            folder_path.join(LEGACY_MANIFEST_FILENAME),
+        )
+    }
 
+    /// The default path of the manifest for the profile with the given name,
+    /// following the same fallback as `path_in_folder`.
+    pub fn profile_path(folder_path: &Path, profile_name: &str) -> PathBuf {
+        prefer_existing(
+            folder_path.join(format!("runway-manifest.{profile_name}.toml")),
+            folder_path.join(format!("tarmac-manifest.{profile_name}.toml")),
+        )
+    }
+
+    /// Reads the manifest at the given path. Manifests written in an older
+    /// format are upgraded in memory. The file itself is left untouched until
+    /// the upgraded manifest is written, like at the end of a sync.
+    pub fn read_from_file<P: AsRef<Path>>(file_path: P) -> Result<Self, ManifestError> {
+        let file_path = file_path.as_ref();
         let contents = fs::read(file_path)?;
-        let config =
-            toml::from_slice(&contents).map_err(|source| ManifestError::DeserializeToml {
-                source,
+
+        Self::parse(&contents, file_path)
+    }
+
+    /// Parses the contents of the manifest at the given path, upgrading it if
+    /// it was written in an older format.
+    fn parse(contents: &[u8], file_path: &Path) -> Result<Self, ManifestError> {
+        let deserialize_error = |source| ManifestError::DeserializeToml {
+            source,
+            file_path: file_path.to_owned(),
+        };
+
+        let mut table: Table = toml::from_slice(contents).map_err(deserialize_error)?;
+
+        let version = match table.get("version") {
+            None => 0,
+            Some(Value::Integer(version)) => {
+                u32::try_from(*version).map_err(|_| ManifestError::InvalidVersion {
+                    file_path: file_path.to_owned(),
+                })?
+            }
+            Some(_) => {
+                return Err(ManifestError::InvalidVersion {
+                    file_path: file_path.to_owned(),
+                })
+            }
+        };
+
+        if version > MANIFEST_VERSION {
+            return Err(ManifestError::TooNew {
                 file_path: file_path.to_owned(),
-            })?;
+                version,
+            });
+        }
+
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(&mut table);
+        }
+
+        table.insert(
+            "version".to_owned(),
+            Value::Integer(MANIFEST_VERSION.into()),
+        );
+
+        if version < MANIFEST_VERSION {
+            log::debug!(
+                "Upgrading manifest {} from version {} to version {}",
+                file_path.display(),
+                version,
+                MANIFEST_VERSION
+            );
+        }
 
-        Ok(config)
+        Value::Table(table).try_into().map_err(deserialize_error)
     }
 
-    pub fn write_to_folder<P: AsRef<Path>>(&self, folder_path: P) -> Result<(), ManifestError> {
-        let folder_path = folder_path.as_ref();
-        let file_path = &folder_path.join(MANIFEST_FILENAME);
+    pub fn write_to_file<P: AsRef<Path>>(&self, file_path: P) -> Result<(), ManifestError> {
+        let file_path = file_path.as_ref();
 
         let serialized = toml::to_vec(self)?;
         fs::write(file_path, serialized)?;
@@ -47,7 +146,45 @@ impl Manifest {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Picks the path with the Runway name, unless only the path with the legacy
+/// Tarmac name exists.
+fn prefer_existing(path: PathBuf, legacy_path: PathBuf) -> PathBuf {
+    if !path.exists() && legacy_path.exists() {
+        legacy_path
+    } else {
+        path
+    }
+}
+
+/// Version 0 manifests may come from Tarmac versions before 0.3.1, which didn't
+/// record hashes or whether inputs were packable. Giving those inputs an empty
+/// hash makes the next sync treat them as changed instead of failing to read
+/// the whole manifest.
+fn migrate_v0_to_v1(manifest: &mut Table) {
+    let Some(Value::Table(inputs)) = manifest.get_mut("inputs") else {
+        return;
+    };
+
+    for (_, input) in inputs.iter_mut() {
+        if let Value::Table(input) = input {
+            input
+                .entry("hash".to_owned())
+                .or_insert_with(|| Value::String(String::new()));
+            input
+                .entry("packable".to_owned())
+                .or_insert(Value::Boolean(false));
+        }
+    }
+}
+
+/// Version 2 manifests record each input's last upload and where it was last
+/// copied by local syncs, in its `upload` and `local` tables. Both are
+/// optional, so version 1 manifests are read as-is. The version changed so
+/// that versions of Runway that don't know about these tables refuse to read
+/// the manifest, instead of dropping them when writing it back.
+fn migrate_v1_to_v2(_manifest: &mut Table) {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct InputManifest {
     /// The hexadecimal encoded hash of the contents of this input the last time
@@ -64,9 +201,106 @@ pub struct InputManifest {
     /// Whether the config applied to this input asked for it to be packed into
     /// a spritesheet.
     pub packable: bool,
+
+    /// Details about the last upload containing this input. Missing for inputs
+    /// synced before Tarmac started recording them.
+    pub upload: Option<UploadRecord>,
+
+    /// Where this input was copied the last time it was synced with the
+    /// `local` target. This is tracked separately from uploads so that local
+    /// and cloud syncs don't undo each other.
+    pub local: Option<LocalInputManifest>,
+}
+
+impl InputManifest {
+    /// The state of this input as of the last time it was uploaded.
+    pub fn uploaded_state(&self) -> SyncedState<'_> {
+        SyncedState {
+            hash: &self.hash,
+            id: self.id.map(AssetId::Id),
+            slice: self.slice,
+            packable: self.packable,
+        }
+    }
+
+    /// The state of this input as of the last time it was synced with the
+    /// `local` target, if it ever was.
+    pub fn local_state(&self) -> Option<SyncedState<'_>> {
+        self.local.as_ref().map(|local| SyncedState {
+            hash: &local.hash,
+            id: Some(AssetId::Path(local.path.clone())),
+            slice: local.slice,
+            packable: local.packable,
+        })
+    }
+}
+
+/// What the manifest remembers about the last time an input was synced with a
+/// particular kind of target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncedState<'a> {
+    pub hash: &'a str,
+    pub id: Option<AssetId>,
+    pub slice: Option<ImageSlice>,
+    pub packable: bool,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LocalInputManifest {
+    /// The hash of the contents of this input the last time it was copied.
+    pub hash: String,
+
+    /// The path this input was copied to, relative to Roblox Studio's content
+    /// folder.
+    pub path: PathBuf,
+
+    /// If the input was packed into a spritesheet, contains the portion of the
+    /// copied image that contains this input.
+    pub slice: Option<ImageSlice>,
+
+    /// Whether the config applied to this input asked for it to be packed into
+    /// a spritesheet.
+    pub packable: bool,
+}
+
+/// Describes an upload, so that audits and cleanup tools can answer basic
+/// questions without hitting the network. For packed inputs, this describes
+/// the whole spritesheet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct UploadRecord {
+    /// Where the image was synced to.
+    pub target: UploadTarget,
+
+    /// The ID of the asset backing the image asset, like the decal that Roblox
+    /// creates alongside it.
+    pub backing_id: Option<u64>,
+
+    /// The width and height of the uploaded image, in pixels.
+    pub dimensions: (u32, u32),
+
+    /// The size of the uploaded image, in bytes.
+    pub size: u64,
+
+    /// When the image was uploaded, formatted as an RFC 3339 timestamp.
+    pub uploaded_at: String,
+
+    /// The group that the image was uploaded to, if any.
+    pub creator_group_id: Option<u64>,
+
+    /// The user that the image was uploaded to, if one was configured.
+    pub creator_user_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UploadTarget {
+    Roblox,
+    Debug,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct ImageSlice {
     coordinates: ((u32, u32), (u32, u32)),
@@ -103,6 +337,16 @@ pub enum ManifestError {
         source: toml::de::Error,
     },
 
+    #[error("Manifest {} has an invalid version", .file_path.display())]
+    InvalidVersion { file_path: PathBuf },
+
+    #[error(
+        "Manifest {} is version {version}, but this version of Runway only supports up to version {}. Please update Runway.",
+        .file_path.display(),
+        MANIFEST_VERSION
+    )]
+    TooNew { file_path: PathBuf, version: u32 },
+
     #[error(transparent)]
     SerializeToml {
         #[from]
@@ -124,3 +368,124 @@ impl ManifestError {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn parse(contents: &str) -> Result<Manifest, ManifestError> {
+        Manifest::parse(contents.as_bytes(), Path::new("runway-manifest.toml"))
+    }
+
+    #[test]
+    fn migrate_version_0() {
+        let manifest = parse(
+            r#"
+                [inputs."icons/close.png"]
+                id = 1234
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.version, MANIFEST_VERSION);
+
+        let input = &manifest.inputs[&AssetName::new("icons/close.png")];
+        assert_eq!(input.hash, "");
+        assert_eq!(input.id, Some(1234));
+        assert!(!input.packable);
+    }
+
+    #[test]
+    fn reading_leaves_file_untouched() {
+        let folder = TempDir::new("manifest-untouched");
+
+        let path = folder.join(MANIFEST_FILENAME);
+        let contents = "[inputs.\"icons/close.png\"]\nid = 1234\n";
+        fs::write(&path, contents).unwrap();
+
+        let manifest = Manifest::read_from_file(&path).unwrap();
+        assert_eq!(manifest.version, MANIFEST_VERSION);
+        assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+    }
+
+    #[test]
+    fn migrate_version_1() {
+        let manifest = parse(
+            r#"
+                version = 1
+
+                [inputs."icons/close.png"]
+                hash = "abc"
+                id = 1234
+                packable = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.version, MANIFEST_VERSION);
+
+        let input = &manifest.inputs[&AssetName::new("icons/close.png")];
+        assert_eq!(input.hash, "abc");
+        assert!(input.packable);
+        assert_eq!(input.upload, None);
+        assert_eq!(input.local, None);
+    }
+
+    #[test]
+    fn current_version() {
+        let manifest = parse(
+            r#"
+                version = 2
+
+                [inputs."icons/close.png"]
+                hash = "abc"
+                id = 1234
+                packable = false
+
+                [inputs."icons/close.png".upload]
+                target = "roblox"
+                dimensions = [64, 64]
+                size = 512
+                uploaded-at = "2024-01-01T00:00:00Z"
+                creator-group-id = 5678
+
+                [inputs."icons/close.png".local]
+                hash = "abc"
+                path = ".tarmac/close.png"
+                packable = false
+            "#,
+        )
+        .unwrap();
+
+        let input = &manifest.inputs[&AssetName::new("icons/close.png")];
+        let upload = input.upload.as_ref().unwrap();
+        assert_eq!(upload.target, UploadTarget::Roblox);
+        assert_eq!(upload.creator_group_id, Some(5678));
+        assert_eq!(
+            input.local.as_ref().unwrap().path,
+            Path::new(".tarmac/close.png")
+        );
+    }
+
+    #[test]
+    fn too_new_version() {
+        let version = MANIFEST_VERSION + 1;
+
+        assert!(matches!(
+            parse(&format!("version = {version}")),
+            Err(ManifestError::TooNew { version: found, .. }) if found == version
+        ));
+    }
+
+    #[test]
+    fn invalid_version() {
+        for contents in ["version = -1", "version = \"1\""] {
+            assert!(
+                matches!(parse(contents), Err(ManifestError::InvalidVersion { .. })),
+                "{}",
+                contents
+            );
+        }
+    }
+}
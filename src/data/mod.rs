@@ -1,7 +1,11 @@
 mod config;
 mod manifest;
+mod stat_cache;
 mod sync;
+mod sync_lock;
 
 pub use config::*;
 pub use manifest::*;
+pub use stat_cache::*;
 pub use sync::*;
+pub use sync_lock::*;
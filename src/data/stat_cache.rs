@@ -0,0 +1,239 @@
+use std::{
+    collections::BTreeMap,
+    fs::Metadata,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Where the stat cache is stored, relative to the folder containing the root
+/// config. This folder only holds local state and shouldn't be committed.
+static STAT_CACHE_PATH: &str = ".runway/stat-cache.json";
+
+/// Files modified this recently aren't cached, because another change within
+/// the same timestamp granularity wouldn't change their modification time.
+const RECENT_CHANGE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Remembers the size, modification time, and hash of every input as of the
+/// last sync, so that inputs that haven't changed since then don't need to be
+/// read and hashed again.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatCache {
+    entries: BTreeMap<PathBuf, StatCacheEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct StatCacheEntry {
+    size: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+    hash: String,
+}
+
+impl StatCache {
+    /// Reads the stat cache of the project in the given folder. A cache that's
+    /// missing or can't be read is treated like an empty one, since it can
+    /// always be rebuilt.
+    pub fn read_from_folder<P: AsRef<Path>>(folder_path: P) -> Self {
+        let file_path = folder_path.as_ref().join(STAT_CACHE_PATH);
+
+        let contents = match fs::read(&file_path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(err) => {
+                log::warn!("Could not read the stat cache, every input will be read: {err}");
+                return Self::default();
+            }
+        };
+
+        match serde_json::from_slice(&contents) {
+            Ok(cache) => cache,
+            Err(err) => {
+                log::warn!(
+                    "Stat cache {} is invalid, every input will be read: {}",
+                    file_path.display(),
+                    err
+                );
+                Self::default()
+            }
+        }
+    }
+
+    pub fn write_to_folder<P: AsRef<Path>>(&self, folder_path: P) -> Result<(), StatCacheError> {
+        let file_path = folder_path.as_ref().join(STAT_CACHE_PATH);
+
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let serialized = serde_json::to_vec(self)?;
+        fs::write(&file_path, serialized)?;
+
+        log::trace!("Saved stat cache to {}", file_path.display());
+
+        Ok(())
+    }
+
+    /// Returns the hash of the file at the given path if its size and
+    /// modification time haven't changed since it was cached.
+    pub fn get(&self, path: &Path, metadata: &Metadata) -> Option<&str> {
+        let entry = self.entries.get(path)?;
+        let (modified_secs, modified_nanos) = modified_time(metadata)?;
+
+        let unchanged = entry.size == metadata.len()
+            && entry.modified_secs == modified_secs
+            && entry.modified_nanos == modified_nanos;
+
+        if unchanged {
+            Some(&entry.hash)
+        } else {
+            None
+        }
+    }
+
+    /// Caches the hash of the file at the given path, unless it was modified
+    /// too recently to be sure that later changes will be noticed.
+    pub fn insert(&mut self, path: PathBuf, metadata: &Metadata, hash: String) {
+        let Some((modified_secs, modified_nanos)) = modified_time(metadata) else {
+            return;
+        };
+
+        let modified = UNIX_EPOCH + Duration::new(modified_secs, modified_nanos);
+        let is_recent = !SystemTime::now()
+            .duration_since(modified)
+            .is_ok_and(|age| age >= RECENT_CHANGE_WINDOW);
+
+        if is_recent {
+            return;
+        }
+
+        self.entries.insert(
+            path,
+            StatCacheEntry {
+                size: metadata.len(),
+                modified_secs,
+                modified_nanos,
+                hash,
+            },
+        );
+    }
+}
+
+fn modified_time(metadata: &Metadata) -> Option<(u64, u32)> {
+    let since_epoch = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+    Some((since_epoch.as_secs(), since_epoch.subsec_nanos()))
+}
+
+#[derive(Debug, Error)]
+pub enum StatCacheError {
+    #[error(transparent)]
+    Json {
+        #[from]
+        source: serde_json::Error,
+    },
+
+    #[error(transparent)]
+    Io {
+        #[from]
+        source: io::Error,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// Writes a file with the given contents and modification time to the
+    /// given folder, returning its path.
+    fn write_file(folder: &TempDir, name: &str, contents: &[u8], modified: SystemTime) -> PathBuf {
+        let path = folder.join(name);
+        fs::write(&path, contents).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        path
+    }
+
+    /// A modification time well outside the recent change window.
+    fn long_ago() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_000_000_000)
+    }
+
+    #[test]
+    fn unchanged_file() {
+        let folder = TempDir::new("stat-cache");
+        let path = write_file(&folder, "unchanged.png", b"contents", long_ago());
+        let metadata = fs::metadata(&path).unwrap();
+
+        let mut cache = StatCache::default();
+        cache.insert(path.clone(), &metadata, "hash".to_owned());
+
+        assert_eq!(
+            cache.get(&path, &fs::metadata(&path).unwrap()),
+            Some("hash")
+        );
+    }
+
+    #[test]
+    fn modified_time_changed() {
+        let folder = TempDir::new("stat-cache");
+        let modified = long_ago();
+        let path = write_file(&folder, "modified-time.png", b"contents", modified);
+
+        let mut cache = StatCache::default();
+        cache.insert(
+            path.clone(),
+            &fs::metadata(&path).unwrap(),
+            "hash".to_owned(),
+        );
+
+        write_file(
+            &folder,
+            "modified-time.png",
+            b"contents",
+            modified + Duration::from_secs(1),
+        );
+        assert_eq!(cache.get(&path, &fs::metadata(&path).unwrap()), None);
+    }
+
+    #[test]
+    fn size_changed() {
+        let folder = TempDir::new("stat-cache");
+        let modified = long_ago();
+        let path = write_file(&folder, "size.png", b"contents", modified);
+
+        let mut cache = StatCache::default();
+        cache.insert(
+            path.clone(),
+            &fs::metadata(&path).unwrap(),
+            "hash".to_owned(),
+        );
+
+        write_file(&folder, "size.png", b"longer contents", modified);
+        assert_eq!(cache.get(&path, &fs::metadata(&path).unwrap()), None);
+    }
+
+    #[test]
+    fn recently_changed_file() {
+        let folder = TempDir::new("stat-cache");
+        let path = write_file(&folder, "recent.png", b"contents", SystemTime::now());
+        let metadata = fs::metadata(&path).unwrap();
+
+        let mut cache = StatCache::default();
+        cache.insert(path.clone(), &metadata, "hash".to_owned());
+
+        assert_eq!(cache.get(&path, &metadata), None);
+    }
+}
@@ -1,8 +1,10 @@
-use std::{fmt, path::PathBuf};
+use std::{borrow::Cow, fmt, io, path::PathBuf};
+
+use fs_err as fs;
 
 use crate::{
     asset_name::AssetName,
-    data::{ImageSlice, InputConfig, InputManifest},
+    data::{ImageSlice, InputConfig, SyncedState, UploadRecord},
 };
 
 use path_slash::PathBufExt;
@@ -30,10 +32,10 @@ impl fmt::Display for AssetId {
     }
 }
 
-/// In-memory representation of a Tarmac Input during the sync process.
+/// In-memory representation of a Runway input during the sync process.
 ///
 /// SyncInput structs are gradually created and filled in from the filesystem,
-/// results of network I/O, and from the previous Tarmac manifest file.
+/// results of network I/O, and from the previous manifest file.
 #[derive(Debug)]
 pub struct SyncInput {
     /// A unique name for this asset in the project.
@@ -52,10 +54,12 @@ pub struct SyncInput {
     /// The configuration that applied to this input when it was discovered.
     pub config: InputConfig,
 
-    /// The contents of the file this input originated from.
-    pub contents: Vec<u8>,
+    /// The contents of the file this input originated from, once they've been
+    /// read. Inputs whose hash came from the stat cache are only read if they
+    /// need to be synced.
+    pub contents: Option<Vec<u8>>,
 
-    /// A hash of `contents`.
+    /// A hash of the file's contents.
     pub hash: String,
 
     /// If this input has been part of an upload to Roblox.com, contains the
@@ -65,11 +69,23 @@ pub struct SyncInput {
     /// If this input has been packed into a spritesheet, contains the slice of
     /// the spritesheet that this input is located in.
     pub slice: Option<ImageSlice>,
+
+    /// Details about the last upload containing this input, if known.
+    pub upload: Option<UploadRecord>,
 }
 
 impl SyncInput {
-    pub fn is_unchanged_since_last_sync(&self, old_manifest: &InputManifest) -> bool {
-        self.hash == old_manifest.hash && self.config.packable == old_manifest.packable
+    /// Returns the contents of the file this input originated from, reading
+    /// them if they haven't been read yet.
+    pub fn read_contents(&self) -> io::Result<Cow<'_, [u8]>> {
+        match &self.contents {
+            Some(contents) => Ok(Cow::Borrowed(contents)),
+            None => Ok(Cow::Owned(fs::read(&self.path)?)),
+        }
+    }
+
+    pub fn is_unchanged_since_last_sync(&self, previous: &SyncedState<'_>) -> bool {
+        self.hash == previous.hash && self.config.packable == previous.packable
     }
 
     /// Creates a non-unique, human-friendly name to refer to this input.
@@ -0,0 +1,204 @@
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    io::{self, Read, Seek, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Keeps other syncs from using a manifest while this one is. The lock is held
+/// by the operating system, so it's released even if Runway exits without
+/// dropping it.
+///
+/// The lock file, like `runway-manifest.toml.lock`, is left next to the
+/// manifest. It's emptied when the lock is dropped, so it only still names a
+/// holder if that sync crashed or was killed.
+#[derive(Debug)]
+pub struct SyncLock {
+    file: File,
+    path: PathBuf,
+    manifest_path: PathBuf,
+}
+
+/// Who holds a lock, which is written to the lock file so that other syncs
+/// can say who they're waiting for.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct LockHolder {
+    pid: u32,
+    host: String,
+}
+
+impl SyncLock {
+    /// Locks the manifest at the given path, failing if another sync already
+    /// has it locked.
+    pub fn acquire(manifest_path: &Path) -> Result<Self, SyncLockError> {
+        let mut file_name = manifest_path.file_name().unwrap_or_default().to_owned();
+        file_name.push(".lock");
+        let path = manifest_path.with_file_name(file_name);
+
+        let io_error = |source| SyncLockError::Io {
+            path: path.clone(),
+            source,
+        };
+
+        // The file isn't truncated until it's locked, since another sync could
+        // be holding it.
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(io_error)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(SyncLockError::Held {
+                    holder: describe_holder(&mut file),
+                    path,
+                })
+            }
+            Err(TryLockError::Error(source)) => return Err(io_error(source)),
+        }
+
+        // Locks are emptied when they're released, so anything left in the
+        // file came from a sync that exited without releasing it.
+        let mut previous = String::new();
+        if file.read_to_string(&mut previous).is_ok() && !previous.trim().is_empty() {
+            log::warn!(
+                "Taking over a stale lock from {}, which didn't finish syncing",
+                describe_contents(&previous)
+            );
+        }
+
+        let holder = LockHolder {
+            pid: process::id(),
+            host: gethostname::gethostname().to_string_lossy().into_owned(),
+        };
+        let contents = serde_json::to_string(&holder).unwrap();
+
+        // Reading a stale lock moved to its end, and writing there would leave
+        // the file padded with zeros in front of the new holder.
+        file.set_len(0).map_err(io_error)?;
+        file.rewind().map_err(io_error)?;
+        file.write_all(contents.as_bytes()).map_err(io_error)?;
+
+        log::trace!("Locked {}", path.display());
+
+        Ok(Self {
+            file,
+            path,
+            manifest_path: manifest_path.to_owned(),
+        })
+    }
+
+    /// The path of the manifest that this lock is for.
+    pub fn manifest_path(&self) -> &Path {
+        &self.manifest_path
+    }
+}
+
+impl Drop for SyncLock {
+    fn drop(&mut self) {
+        if let Err(err) = self.file.set_len(0) {
+            log::warn!("Could not release lock {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+fn describe_holder(file: &mut File) -> String {
+    let mut contents = String::new();
+
+    // Some platforms don't allow reading a file that another process locked.
+    match file.read_to_string(&mut contents) {
+        Ok(_) => describe_contents(&contents),
+        Err(_) => "another process".to_owned(),
+    }
+}
+
+fn describe_contents(contents: &str) -> String {
+    match serde_json::from_str::<LockHolder>(contents) {
+        Ok(holder) => format!("PID {} on {}", holder.pid, holder.host),
+        Err(_) => "another process".to_owned(),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SyncLockError {
+    #[error(
+        "Another sync is already using this project's manifest: {holder} holds {}",
+        .path.display()
+    )]
+    Held { path: PathBuf, holder: String },
+
+    #[error("Could not lock {}", .path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn lock_path(folder: &TempDir) -> PathBuf {
+        folder.join("runway-manifest.toml.lock")
+    }
+
+    fn this_holder() -> String {
+        format!(
+            "PID {} on {}",
+            process::id(),
+            gethostname::gethostname().to_string_lossy()
+        )
+    }
+
+    #[test]
+    fn acquire_free_lock() {
+        let folder = TempDir::new("sync-lock-free");
+        let lock = SyncLock::acquire(&folder.join("runway-manifest.toml")).unwrap();
+
+        let contents = fs::read_to_string(lock_path(&folder)).unwrap();
+        assert_eq!(describe_contents(&contents), this_holder());
+
+        drop(lock);
+        assert_eq!(fs::read_to_string(lock_path(&folder)).unwrap(), "");
+    }
+
+    #[test]
+    fn acquire_held_lock() {
+        let folder = TempDir::new("sync-lock-held");
+        let manifest_path = folder.join("runway-manifest.toml");
+        let _lock = SyncLock::acquire(&manifest_path).unwrap();
+
+        // Locks belong to each opened file, so even this process can't take
+        // the lock a second time.
+        match SyncLock::acquire(&manifest_path) {
+            Err(SyncLockError::Held { path, holder }) => {
+                assert_eq!(path, lock_path(&folder));
+                assert_eq!(holder, this_holder());
+            }
+            other => panic!("expected the lock to be held, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn take_over_stale_lock() {
+        let folder = TempDir::new("sync-lock-stale");
+        fs::write(
+            lock_path(&folder),
+            r#"{"pid":1234,"host":"crashed-machine"}"#,
+        )
+        .unwrap();
+
+        let _lock = SyncLock::acquire(&folder.join("runway-manifest.toml")).unwrap();
+
+        let contents = fs::read_to_string(lock_path(&folder)).unwrap();
+        assert_eq!(describe_contents(&contents), this_holder());
+    }
+}
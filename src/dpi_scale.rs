@@ -23,28 +23,27 @@ impl DpiAwarePathInfo {
 /// Given a path, extracts its intended DPI scale and constructs a path without
 /// DPI scale information in it. This can be used to group together multiple
 /// versions of the same image.
+///
+/// Paths without a file name that's valid Unicode, or with a scale too large to
+/// be represented, are treated as having no DPI scale.
 pub(crate) fn extract_path_info<P: AsRef<Path>>(path: P) -> DpiAwarePathInfo {
     lazy_static::lazy_static! {
         static ref DPI_PATTERN: Regex = Regex::new(r"^(.+?)@(\d+)x(.+?)$").unwrap();
     }
 
     let path = path.as_ref();
+    let file_name = path.file_name().and_then(|name| name.to_str());
 
-    let file_name = match path.file_name().unwrap().to_str() {
-        Some(name) => name,
+    let captures = file_name.and_then(|name| DPI_PATTERN.captures(name));
+    let scaled = captures.and_then(|captures| {
+        let dpi_scale = captures.get(2).unwrap().as_str().parse().ok()?;
+        Some((captures, dpi_scale))
+    });
 
-        // If the filename isn't valid Unicode, this is an error.
-        None => {
-            panic!("Path {} had invalid Unicode", path.display());
-        }
-    };
-
-    match DPI_PATTERN.captures(file_name) {
-        Some(captures) => {
-            let file_stem = captures.get(1).unwrap().as_str().to_owned();
-            let scale_str = captures.get(2).unwrap().as_str();
+    match scaled {
+        Some((captures, dpi_scale)) => {
+            let file_stem = captures.get(1).unwrap().as_str();
             let suffix = captures.get(3).unwrap().as_str();
-            let dpi_scale = scale_str.parse().unwrap();
 
             let file_name_without_dpi_scale = format!("{}{}", file_stem, suffix);
             let path_without_dpi_scale = path.with_file_name(file_name_without_dpi_scale);
@@ -128,4 +127,14 @@ mod test {
             DpiAwarePathInfo::new("backup-your-stuff.png.bak", 4)
         );
     }
+
+    #[test]
+    fn unrepresentable_scale() {
+        assert_eq!(
+            extract_path_info("huge@99999999999x.png"),
+            DpiAwarePathInfo::new("huge@99999999999x.png", 1)
+        );
+
+        assert_eq!(extract_path_info(".."), DpiAwarePathInfo::new("..", 1));
+    }
 }
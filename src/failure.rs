@@ -0,0 +1,150 @@
+/*
+ * Copyright (c) 2024 Paradoxum Games
+ * This file is licensed under the Mozilla Public License (MPL-2.0). A copy of it is available in the 'LICENSE' file at the root of the repository.
+*/
+
+use reqwest::StatusCode;
+
+use crate::{
+    commands::SyncError,
+    data::{ConfigError, ManifestError},
+    roblox_api::RobloxApiError,
+    sync_backend::Error as SyncBackendError,
+};
+
+/// The kinds of failure that Runway exits with a distinct code for, so that
+/// scripts can react to them differently. Runway exits with code 0 when a
+/// command succeeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Any failure without a more specific kind. Exits with code 1.
+    Other,
+
+    /// A config or manifest couldn't be read or isn't valid. Exits with code 2.
+    Config,
+
+    /// No credentials were found, or Roblox didn't accept them. Exits with
+    /// code 3.
+    Auth,
+
+    /// Roblox rate limited uploads, so syncing stopped early. Exits with code
+    /// 4.
+    RateLimited,
+
+    /// Syncing finished, but some inputs couldn't be synced. Exits with code 5.
+    Partial,
+}
+
+impl Failure {
+    /// Finds the kind of failure that the given error is.
+    pub fn of(error: &anyhow::Error) -> Self {
+        if let Some(error) = error.downcast_ref::<SyncError>() {
+            Self::of_sync_error(error)
+        } else if let Some(error) = error.downcast_ref::<SyncBackendError>() {
+            Self::of_backend_error(error)
+        } else if let Some(error) = error.downcast_ref::<RobloxApiError>() {
+            Self::of_api_error(error)
+        } else if error.is::<ConfigError>() || error.is::<ManifestError>() {
+            Self::Config
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Finds the kind of failure of a sync that finished with the given
+    /// errors. Being rate limited or rejected by Roblox takes precedence over
+    /// other errors, since they're likely why other inputs failed too.
+    pub fn of_sync_errors(errors: &[anyhow::Error]) -> Self {
+        let failures: Vec<_> = errors.iter().map(Self::of).collect();
+
+        if failures.contains(&Self::RateLimited) {
+            Self::RateLimited
+        } else if failures.contains(&Self::Auth) {
+            Self::Auth
+        } else {
+            Self::Partial
+        }
+    }
+
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Other => 1,
+            Self::Config => 2,
+            Self::Auth => 3,
+            Self::RateLimited => 4,
+            Self::Partial => 5,
+        }
+    }
+
+    fn of_sync_error(error: &SyncError) -> Self {
+        match error {
+            SyncError::HadErrors { failure, .. } => *failure,
+            SyncError::Config { .. }
+            | SyncError::Manifest { .. }
+            | SyncError::OverlappingGlobs { .. } => Self::Config,
+            SyncError::Backend { source } => Self::of_backend_error(source),
+            SyncError::RobloxApi { source } => Self::of_api_error(source),
+            _ => Self::Other,
+        }
+    }
+
+    fn of_backend_error(error: &SyncBackendError) -> Self {
+        match error {
+            SyncBackendError::RateLimited => Self::RateLimited,
+            SyncBackendError::RobloxError { source } => Self::of_api_error(source),
+            _ => Self::Other,
+        }
+    }
+
+    fn of_api_error(error: &RobloxApiError) -> Self {
+        match error {
+            RobloxApiError::MissingAuth
+            | RobloxApiError::MissingCsrfToken
+            | RobloxApiError::ResponseError {
+                status: StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN,
+                ..
+            } => Self::Auth,
+            RobloxApiError::ResponseError {
+                status: StatusCode::TOO_MANY_REQUESTS,
+                ..
+            } => Self::RateLimited,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rate_limited_uploads() {
+        let error = anyhow::Error::from(SyncBackendError::RateLimited);
+        assert_eq!(Failure::of(&error), Failure::RateLimited);
+
+        let error = anyhow::Error::from(SyncError::from(SyncBackendError::RateLimited));
+        assert_eq!(Failure::of(&error), Failure::RateLimited);
+    }
+
+    #[test]
+    fn rejected_credentials() {
+        let error = anyhow::Error::from(RobloxApiError::ResponseError {
+            status: StatusCode::FORBIDDEN,
+            body: String::new(),
+        });
+        assert_eq!(Failure::of(&error), Failure::Auth);
+    }
+
+    #[test]
+    fn sync_errors_prefer_rate_limiting() {
+        let errors = vec![
+            anyhow::Error::from(RobloxApiError::MissingAuth),
+            anyhow::Error::from(SyncBackendError::RateLimited),
+            anyhow::anyhow!("something else"),
+        ];
+        assert_eq!(Failure::of_sync_errors(&errors), Failure::RateLimited);
+
+        let errors = vec![anyhow::anyhow!("something else")];
+        assert_eq!(Failure::of_sync_errors(&errors), Failure::Partial);
+    }
+}
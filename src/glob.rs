@@ -1,12 +1,18 @@
-//! Wrapper around globset's Glob type that has better serialization
-//! characteristics by coupling Glob and GlobMatcher into a single type.
+//! Wrappers around globset's types that have better serialization
+//! characteristics: a single glob, and a list of globs that are matched
+//! together by a `GlobSet`.
 
 use std::{
     fmt,
     path::{Path, PathBuf},
 };
 
-use globset::{Glob as InnerGlob, GlobMatcher};
+use globset::{Glob as InnerGlob, GlobSet, GlobSetBuilder};
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject, SubschemaValidation},
+    JsonSchema,
+};
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 
 pub use globset::Error;
@@ -14,19 +20,13 @@ pub use globset::Error;
 #[derive(Debug, Clone)]
 pub struct Glob {
     inner: InnerGlob,
-    matcher: GlobMatcher,
 }
 
 impl Glob {
     pub fn new(glob: &str) -> Result<Self, Error> {
         let inner = InnerGlob::new(glob)?;
-        let matcher = inner.compile_matcher();
 
-        Ok(Glob { inner, matcher })
-    }
-
-    pub fn is_match<P: AsRef<Path>>(&self, path: P) -> bool {
-        self.matcher.is_match(path)
+        Ok(Glob { inner })
     }
 
     pub fn get_prefix(&self) -> PathBuf {
@@ -50,9 +50,23 @@ impl Serialize for Glob {
 
 impl<'de> Deserialize<'de> for Glob {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let glob = <&str as Deserialize>::deserialize(deserializer)?;
+        let glob = String::deserialize(deserializer)?;
+
+        Glob::new(&glob).map_err(D::Error::custom)
+    }
+}
+
+impl JsonSchema for Glob {
+    fn schema_name() -> String {
+        "Glob".to_owned()
+    }
 
-        Glob::new(glob).map_err(D::Error::custom)
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            ..Default::default()
+        }
+        .into()
     }
 }
 
@@ -62,6 +76,122 @@ impl fmt::Display for Glob {
     }
 }
 
+/// A list of globs that match a path when any of them do. In config files it
+/// can be written as either a single glob or a list of globs.
+#[derive(Debug, Clone)]
+pub struct GlobList {
+    globs: Vec<Glob>,
+    set: GlobSet,
+}
+
+impl GlobList {
+    pub fn new(globs: Vec<Glob>) -> Result<Self, Error> {
+        let mut builder = GlobSetBuilder::new();
+        for glob in &globs {
+            builder.add(glob.inner.clone());
+        }
+
+        let set = builder.build()?;
+
+        Ok(GlobList { globs, set })
+    }
+
+    pub fn is_match<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.set.is_match(path)
+    }
+
+    /// The longest path that every glob in this list starts with, which is
+    /// where a search for matching files needs to start.
+    pub fn get_prefix(&self) -> PathBuf {
+        let mut prefixes = self.globs.iter().map(Glob::get_prefix);
+
+        let Some(first) = prefixes.next() else {
+            return PathBuf::new();
+        };
+
+        prefixes.fold(first, |prefix, other| common_prefix(&prefix, &other))
+    }
+}
+
+impl Default for GlobList {
+    fn default() -> Self {
+        GlobList {
+            globs: Vec::new(),
+            set: GlobSet::empty(),
+        }
+    }
+}
+
+impl PartialEq for GlobList {
+    fn eq(&self, other: &Self) -> bool {
+        self.globs == other.globs
+    }
+}
+
+impl Eq for GlobList {}
+
+impl Serialize for GlobList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // A single glob is written the same way as before lists were allowed.
+        match self.globs.as_slice() {
+            [glob] => glob.serialize(serializer),
+            globs => globs.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for GlobList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMany {
+            One(Glob),
+            Many(Vec<Glob>),
+        }
+
+        let globs = match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(glob) => vec![glob],
+            OneOrMany::Many(globs) => globs,
+        };
+
+        GlobList::new(globs).map_err(D::Error::custom)
+    }
+}
+
+impl JsonSchema for GlobList {
+    fn schema_name() -> String {
+        "GlobList".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![
+                    gen.subschema_for::<Glob>(),
+                    gen.subschema_for::<Vec<Glob>>(),
+                ]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl fmt::Display for GlobList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, glob) in self.globs.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+
+            glob.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
 // A basic set of characters that might indicate the use of glob pattern syntax.
 // This is to distinguish portions of a glob that are fixed paths (e.g.
 // "foo.png") from ones that are leveraging patterns (e.g. "*.png").
@@ -91,6 +221,14 @@ fn get_non_pattern_prefix(glob_path: &Path) -> PathBuf {
     prefix
 }
 
+fn common_prefix(a: &Path, b: &Path) -> PathBuf {
+    a.components()
+        .zip(b.components())
+        .take_while(|(a, b)| a == b)
+        .map(|(component, _)| component)
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -126,4 +264,26 @@ mod test {
             PathBuf::from("a/b/foo.png")
         )
     }
+
+    #[test]
+    fn list_prefix() {
+        let globs = GlobList::new(vec![
+            Glob::new("a/b/c/*.png").unwrap(),
+            Glob::new("a/b/d/**/*.png").unwrap(),
+        ])
+        .unwrap();
+
+        assert_eq!(globs.get_prefix(), PathBuf::from("a/b"));
+    }
+
+    #[test]
+    fn list_no_common_prefix() {
+        let globs = GlobList::new(vec![
+            Glob::new("a/*.png").unwrap(),
+            Glob::new("b/*.png").unwrap(),
+        ])
+        .unwrap();
+
+        assert_eq!(globs.get_prefix(), PathBuf::from(""));
+    }
 }
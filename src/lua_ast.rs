@@ -49,6 +49,12 @@ impl From<Statement> for Block {
     }
 }
 
+impl From<Vec<Statement>> for Block {
+    fn from(statements: Vec<Statement>) -> Self {
+        Self { statements }
+    }
+}
+
 impl FmtLua for Block {
     fn fmt_lua(&self, output: &mut LuaStream<'_>) -> fmt::Result {
         for statement in &self.statements {
@@ -65,6 +71,11 @@ proxy_display!(Block);
 pub(crate) enum Statement {
     Return(Expression),
     If(IfBlock),
+    Local(String, Expression),
+
+    /// Used as a catch-all for when this module doesn't define a primitive we
+    /// need for codegen.
+    Raw(String),
 }
 
 impl FmtLua for Statement {
@@ -100,6 +111,11 @@ impl FmtLua for Statement {
 
                 write!(output, "end")
             }
+            Self::Local(name, value) => {
+                write!(output, "local {name} = ")?;
+                value.fmt_lua(output)
+            }
+            Self::Raw(inner) => output.write_str(inner),
         }
     }
 }
@@ -18,11 +18,16 @@ mod codegen;
 mod commands;
 mod data;
 mod dpi_scale;
+mod failure;
 mod glob;
 mod lua_ast;
 mod options;
+mod progress;
 mod roblox_api;
 mod sync_backend;
+#[cfg(test)]
+mod test_util;
+mod upload_cache;
 
 use std::{env, panic, process};
 
@@ -32,10 +37,23 @@ use clap::Parser;
 use tokio::signal;
 
 use crate::commands::Command;
+use crate::failure::Failure;
 use crate::options::Options;
 
 async fn run(options: Options) -> Result<(), anyhow::Error> {
-    match options.command {
+    let output_format = options.global.output_format;
+
+    // `sync`, `watch`, and `check` print their errors as part of their own
+    // reports, which the other commands that print results don't have.
+    let prints_errors = matches!(
+        options.command,
+        Command::UploadImage(_)
+            | Command::DownloadImage(_)
+            | Command::AssetList(_)
+            | Command::CreateCacheMap(_)
+    );
+
+    let result = match options.command {
         Command::UploadImage(sub_options) => {
             commands::upload_image(options.global, sub_options).await
         }
@@ -43,13 +61,26 @@ async fn run(options: Options) -> Result<(), anyhow::Error> {
             commands::download_image(options.global, sub_options).await
         }
         Command::Sync(sub_options) => commands::sync(options.global, sub_options).await,
+        Command::Watch(sub_options) => commands::watch(options.global, sub_options).await,
+        Command::Check(sub_options) => commands::check(options.global, sub_options),
         Command::CreateCacheMap(sub_options) => {
             commands::create_cache_map(options.global, sub_options).await
         }
         Command::AssetList(sub_options) => commands::asset_list(options.global, sub_options).await,
-    }?;
+        Command::MergeManifest(sub_options) => {
+            commands::merge_manifest(options.global, sub_options)
+        }
+        Command::Migrate(sub_options) => commands::migrate(options.global, sub_options),
+        Command::Schema(sub_options) => commands::schema(options.global, sub_options),
+    };
+
+    if let Err(err) = &result {
+        if prints_errors {
+            output_format.print_error(err)?;
+        }
+    }
 
-    Ok(())
+    result
 }
 
 #[tokio::main]
@@ -97,29 +128,41 @@ async fn main() {
         process::exit(1);
     }));
 
-    let options = Options::parse();
+    let mut options = Options::parse();
+    options.global = options.global.with_legacy_api_key();
 
     let log_filter = match options.global.verbosity {
         0 => "info",
-        1 => "info,tarmac=debug",
-        2 => "info,tarmac=trace",
+        1 => "info,runway=debug",
+        2 => "info,runway=trace",
         _ => "trace",
     };
 
     let log_env = env_logger::Env::default().default_filter_or(log_filter);
 
-    env_logger::Builder::from_env(log_env)
+    let logger = env_logger::Builder::from_env(log_env)
         .format_module_path(false)
         .format_timestamp(None)
         // Indent following lines equal to the log level label, like `[ERROR] `
         .format_indent(Some(8))
-        .init();
+        .build();
+
+    progress::init_logger(logger);
+
+    if let Some(threads) = options.global.threads {
+        if let Err(err) = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+        {
+            log::warn!("Could not set the number of threads: {err}");
+        }
+    }
 
     tokio::select! {
         result = run(options) => {
             if let Err(err) = result {
                 log::error!("command exited with error {err:?}");
-                process::exit(1);
+                process::exit(Failure::of(&err).exit_code());
             }
         },
         _ = signal::ctrl_c() => {
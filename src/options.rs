@@ -9,9 +9,19 @@
  * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 */
 
+use std::{
+    env,
+    io::{self, Write},
+};
+
 use crate::commands::Command;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use secrecy::SecretString;
+use serde::Serialize;
+
+/// The environment variable that Tarmac read the Open Cloud API key from,
+/// which is still used if `RUNWAY_API_KEY` isn't set.
+static LEGACY_API_KEY_VAR: &str = "TARMAC_API_KEY";
 
 #[derive(Debug, Parser)]
 #[clap(about = env!("CARGO_PKG_DESCRIPTION"))]
@@ -25,26 +35,91 @@ pub struct Options {
 
 #[derive(Debug, Parser)]
 pub struct Global {
-    /// The authentication cookie for Tarmac to use. If not specified, Tarmac
+    /// The authentication cookie for Runway to use. If not specified, Runway
     /// will attempt to use the cookie from the Roblox Studio installation on
     /// the system.
     #[clap(long, global(true), conflicts_with("api_key"))]
     pub auth: Option<SecretString>,
 
-    /// The Open Cloud API key for Tarmac to use. If not specified, Tarmac
+    /// The Open Cloud API key for Runway to use. If not specified, Runway
     /// will attempt to use the API key stored in the environment variable
-    /// 'TARMAC_API_KEY'.
+    /// 'RUNWAY_API_KEY', or the one that Tarmac used if that isn't set.
     #[clap(
         long,
         global(true),
-        env("TARMAC_API_KEY"),
+        env("RUNWAY_API_KEY"),
         hide_env_values(true),
         conflicts_with("auth")
     )]
     pub api_key: Option<SecretString>,
 
+    /// The number of threads to use for hashing and processing images. If not
+    /// specified, one thread is used for each CPU core.
+    #[clap(long, global(true))]
+    pub threads: Option<usize>,
+
+    /// How commands report their results. With 'json', commands print a
+    /// single JSON document describing what they did to stdout, and logs are
+    /// still written to stderr.
+    #[clap(long, global(true), value_enum, default_value_t)]
+    pub output_format: OutputFormat,
+
     /// Sets verbosity level. Can be specified multiple times to increase the verbosity
     /// of this program.
     #[clap(long = "verbose", short, global(true), action(clap::ArgAction::Count))]
     pub verbosity: u8,
 }
+
+impl Global {
+    /// Reads the API key from the legacy `TARMAC_API_KEY` environment variable
+    /// if no other authentication method was given.
+    pub fn with_legacy_api_key(mut self) -> Self {
+        if self.auth.is_none() && self.api_key.is_none() {
+            self.api_key = env::var(LEGACY_API_KEY_VAR)
+                .ok()
+                .filter(|key| !key.is_empty())
+                .map(SecretString::new);
+        }
+
+        self
+    }
+}
+
+/// How commands report their results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Results are only logged for people to read.
+    #[default]
+    Text,
+
+    /// Results are also printed to stdout as JSON, for scripts to read.
+    Json,
+}
+
+impl OutputFormat {
+    /// Prints a command's result to stdout if results are printed as JSON.
+    pub fn print_result<T: Serialize>(self, result: &T) -> anyhow::Result<()> {
+        if self == Self::Json {
+            let mut stdout = io::stdout().lock();
+            serde_json::to_writer_pretty(&mut stdout, result)?;
+            writeln!(stdout)?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints the error that a command failed with to stdout if results are
+    /// printed as JSON, so that scripts still get a result to read.
+    pub fn print_error(self, error: &anyhow::Error) -> anyhow::Result<()> {
+        self.print_result(&ErrorReport {
+            errors: vec![format!("{error:#}")],
+        })
+    }
+}
+
+/// The result of a command that failed, printed when using JSON output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ErrorReport {
+    errors: Vec<String>,
+}
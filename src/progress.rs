@@ -0,0 +1,167 @@
+/*
+ * Copyright (c) 2024 Paradoxum Games
+ * This file is licensed under the Mozilla Public License (MPL-2.0). A copy of it is available in the 'LICENSE' file at the root of the repository.
+*/
+
+//! Progress reporting for long-running work. When stderr is a terminal,
+//! progress is drawn as bars. Otherwise, like in CI, progress is logged every
+//! so often instead, so that long phases still show that they're moving.
+
+use std::{
+    fmt,
+    io::{self, IsTerminal},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        LazyLock, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use indicatif::{HumanDuration, MultiProgress, ProgressBar, ProgressState, ProgressStyle};
+use log::{Log, Metadata, Record};
+
+/// How often progress is logged when it isn't drawn as bars.
+const LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Where progress bars are drawn, or `None` if stderr isn't a terminal.
+static BARS: LazyLock<Option<MultiProgress>> =
+    LazyLock::new(|| io::stderr().is_terminal().then(MultiProgress::new));
+
+/// Installs the given logger, hiding progress bars while each line is logged
+/// so that lines aren't drawn over by bars.
+pub fn init_logger(logger: env_logger::Logger) {
+    let max_level = logger.filter();
+
+    log::set_boxed_logger(Box::new(ProgressLogger(logger)))
+        .expect("the logger should only be installed once");
+    log::set_max_level(max_level);
+}
+
+struct ProgressLogger(env_logger::Logger);
+
+impl Log for ProgressLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.0.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.0.matches(record) {
+            return;
+        }
+
+        if let Some(bars) = &*BARS {
+            bars.suspend(|| self.0.log(record));
+        } else {
+            self.0.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.0.flush();
+    }
+}
+
+/// Reports progress through one phase of work, like hashing inputs. The
+/// phase is finished when this is dropped. Progress can be reported from
+/// multiple threads at once.
+pub struct Progress {
+    phase: &'static str,
+    total: Option<u64>,
+    started: Instant,
+    position: AtomicU64,
+    last_logged: Mutex<Instant>,
+    bar: Option<ProgressBar>,
+}
+
+impl Progress {
+    /// Starts a phase made up of the given number of steps, or of a number of
+    /// steps that isn't known ahead of time.
+    pub fn new(phase: &'static str, total: Option<u64>) -> Self {
+        let bar = BARS.as_ref().map(|bars| {
+            let bar = if let Some(total) = total {
+                ProgressBar::new(total).with_style(
+                    style("{prefix:>8} [{bar:30}] {pos}/{len} ({rate}, ETA {eta})")
+                        .progress_chars("=> "),
+                )
+            } else {
+                let bar = ProgressBar::new_spinner()
+                    .with_style(style("{prefix:>8} {spinner} {pos} ({rate})"));
+                bar.enable_steady_tick(Duration::from_millis(100));
+                bar
+            };
+
+            bars.add(bar.with_prefix(phase))
+        });
+
+        let started = Instant::now();
+
+        Self {
+            phase,
+            total,
+            started,
+            position: AtomicU64::new(0),
+            last_logged: Mutex::new(started),
+            bar,
+        }
+    }
+
+    /// Records that the given number of steps were finished.
+    pub fn inc(&self, delta: u64) {
+        let position = self.position.fetch_add(delta, Ordering::Relaxed) + delta;
+
+        if let Some(bar) = &self.bar {
+            bar.inc(delta);
+            return;
+        }
+
+        // Only one thread needs to log progress, so threads that find another
+        // one checking don't wait for it.
+        let Ok(mut last_logged) = self.last_logged.try_lock() else {
+            return;
+        };
+
+        if last_logged.elapsed() >= LOG_INTERVAL {
+            *last_logged = Instant::now();
+            log::info!("{}", self.describe(position));
+        }
+    }
+
+    // Counts of inputs and files are far smaller than the range that f64 can
+    // represent exactly.
+    #[allow(clippy::cast_precision_loss)]
+    fn describe(&self, position: u64) -> String {
+        let phase = self.phase;
+        let per_second = position as f64 / self.started.elapsed().as_secs_f64();
+
+        match self.total {
+            Some(total) if per_second > 0.0 => {
+                let remaining = total.saturating_sub(position) as f64 / per_second;
+                let eta = HumanDuration(Duration::from_secs_f64(remaining));
+
+                format!("{phase}: {position}/{total} ({per_second:.1}/s, about {eta} left)")
+            }
+            Some(total) => format!("{phase}: {position}/{total}"),
+            None => format!("{phase}: {position} ({per_second:.1}/s)"),
+        }
+    }
+}
+
+/// Creates a style for progress bars from the given template, which can also
+/// use `{rate}` for the number of steps finished per second.
+fn style(template: &str) -> ProgressStyle {
+    ProgressStyle::with_template(template).unwrap().with_key(
+        "rate",
+        |state: &ProgressState, writer: &mut dyn fmt::Write| {
+            write!(writer, "{:.1}/s", state.per_sec()).unwrap();
+        },
+    )
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if let (Some(bars), Some(bar)) = (&*BARS, &self.bar) {
+            bar.finish_and_clear();
+            bars.remove(bar);
+        }
+    }
+}
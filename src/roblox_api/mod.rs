@@ -85,7 +85,7 @@ pub enum RobloxApiError {
     #[error("Failed to retrieve asset ID from Roblox cloud")]
     AssetGetFailed,
 
-    #[error("Tarmac is unable to locate an authentication method")]
+    #[error("Runway is unable to locate an authentication method")]
     MissingAuth,
 
     #[error("Operation path is missing")]
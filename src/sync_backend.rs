@@ -26,8 +26,10 @@ use thiserror::Error as ThisError;
 use tokio::sync::RwLock;
 
 use crate::{
+    commands::generate_asset_hash,
     data::AssetId,
     roblox_api::{ImageUploadData, RobloxApiClient, RobloxApiError},
+    upload_cache::{CachedUpload, UploadCache, UploadCacheKey},
 };
 
 #[async_trait]
@@ -38,6 +40,9 @@ pub trait SyncBackend {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct UploadResponse {
     pub id: AssetId,
+
+    /// The ID of the asset backing `id`, if the backend created one.
+    pub backing_id: Option<u64>,
 }
 
 #[derive(Clone, Debug)]
@@ -68,8 +73,8 @@ impl SyncBackend for RobloxSyncBackend {
             .api_client
             .upload_image(ImageUploadData {
                 image_data: Cow::Owned(data.contents),
-                name: "TarmacImage".to_string(),
-                description: "Uploaded by Tarmac.".to_string(),
+                name: "RunwayImage".to_string(),
+                description: "Uploaded by Runway.".to_string(),
             })
             .await;
 
@@ -79,6 +84,7 @@ impl SyncBackend for RobloxSyncBackend {
 
                 Ok(UploadResponse {
                     id: AssetId::Id(response.asset_id),
+                    backing_id: Some(response.backing_asset_id),
                 })
             }
 
@@ -148,6 +154,7 @@ impl SyncBackend for LocalSyncBackend {
 
         Ok(UploadResponse {
             id: AssetId::Path(asset_path),
+            backing_id: None,
         })
     }
 }
@@ -190,6 +197,7 @@ impl SyncBackend for DebugSyncBackend {
 
         Ok(UploadResponse {
             id: AssetId::Id(id),
+            backing_id: None,
         })
     }
 }
@@ -227,7 +235,7 @@ impl SyncBackend for RetryBackend {
         for index in 0..self.attempts {
             if index != 0 {
                 log::info!(
-                    "tarmac is being rate limited, retrying upload ({}/{})",
+                    "runway is being rate limited, retrying upload ({}/{})",
                     index,
                     self.attempts - 1
                 );
@@ -244,12 +252,79 @@ impl SyncBackend for RetryBackend {
     }
 }
 
+/// Looks up images in a shared upload cache before uploading them with another
+/// `SyncBackend`, and records the images it uploads in the cache. Problems with
+/// the cache are only logged, since the images can still be uploaded.
+pub struct UploadCacheBackend {
+    inner: Box<dyn SyncBackend + Sync + Send + 'static>,
+    cache: Box<dyn UploadCache + Sync + Send>,
+    creator: String,
+}
+
+impl UploadCacheBackend {
+    /// Creates a new backend from another `SyncBackend`. The creator identifies
+    /// who images are uploaded to, since assets can't be shared between them.
+    pub fn new(
+        inner: Box<dyn SyncBackend + Sync + Send + 'static>,
+        cache: Box<dyn UploadCache + Sync + Send>,
+        creator: String,
+    ) -> Self {
+        Self {
+            inner,
+            cache,
+            creator,
+        }
+    }
+}
+
+#[async_trait]
+impl SyncBackend for UploadCacheBackend {
+    async fn upload(&self, data: UploadInfo) -> Result<UploadResponse> {
+        let key = UploadCacheKey {
+            creator: self.creator.clone(),
+            hash: generate_asset_hash(&data.contents),
+        };
+
+        match self.cache.get(&key) {
+            Ok(Some(upload)) => {
+                log::info!(
+                    "Found {} in the upload cache with ID {}",
+                    data.name,
+                    upload.id
+                );
+
+                return Ok(UploadResponse {
+                    id: AssetId::Id(upload.id),
+                    backing_id: upload.backing_id,
+                });
+            }
+            Ok(None) => {}
+            Err(err) => log::warn!("Could not read from the upload cache: {err}"),
+        }
+
+        let response = self.inner.upload(data).await?;
+
+        if let AssetId::Id(id) = response.id {
+            let upload = CachedUpload {
+                id,
+                backing_id: response.backing_id,
+            };
+
+            if let Err(err) = self.cache.insert(&key, upload) {
+                log::warn!("Could not write to the upload cache: {err}");
+            }
+        }
+
+        Ok(response)
+    }
+}
+
 #[derive(Debug, ThisError)]
 pub enum Error {
     #[error("Cannot upload assets with the 'none' target.")]
     NoneBackend,
 
-    #[error("Tarmac was rate-limited trying to upload assets. Try again in a little bit.")]
+    #[error("Runway was rate-limited trying to upload assets. Try again in a little bit.")]
     RateLimited,
 
     #[error(transparent)]
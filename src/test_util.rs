@@ -0,0 +1,53 @@
+/*
+ * Copyright (c) 2024 Paradoxum Games
+ * This file is licensed under the Mozilla Public License (MPL-2.0). A copy of it is available in the 'LICENSE' file at the root of the repository.
+*/
+
+//! Helpers shared by tests.
+
+use std::{
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use fs_err as fs;
+
+/// Counts the folders made so far, so that every folder made by a test
+/// process gets its own name.
+static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+/// A folder in the system's temporary folder that's removed along with
+/// everything in it when this is dropped.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Makes a new, empty folder whose name includes the given name, to tell
+    /// which test left it behind if it can't be removed.
+    pub fn new(name: &str) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("runway-test-{}-{id}-{name}", process::id()));
+
+        // A folder left by an earlier process with the same ID is stale.
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.path.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
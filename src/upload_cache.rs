@@ -0,0 +1,218 @@
+/*
+ * Copyright (c) 2024 Paradoxum Games
+ * This file is licensed under the Mozilla Public License (MPL-2.0). A copy of it is available in the 'LICENSE' file at the root of the repository.
+*/
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process,
+};
+
+use fs_err as fs;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// An image that was uploaded by anyone sharing the upload cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CachedUpload {
+    pub id: u64,
+    pub backing_id: Option<u64>,
+}
+
+/// Identifies an upload by the creator it was uploaded to and the hash of the
+/// uploaded contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadCacheKey {
+    pub creator: String,
+    pub hash: String,
+}
+
+impl UploadCacheKey {
+    fn relative_path(&self) -> String {
+        format!("{}/{}", self.creator, self.hash)
+    }
+}
+
+/// Remembers which assets images were uploaded to, so that an image uploaded
+/// by one person working on a project isn't uploaded again by everyone else.
+pub trait UploadCache {
+    fn get(&self, key: &UploadCacheKey) -> Result<Option<CachedUpload>, UploadCacheError>;
+
+    fn insert(&self, key: &UploadCacheKey, upload: CachedUpload) -> Result<(), UploadCacheError>;
+}
+
+/// Opens the upload cache at the given location. Locations starting with
+/// `http://` or `https://` are HTTP services, and anything else is a folder,
+/// relative to the given base path.
+pub fn open_upload_cache(location: &str, base_path: &Path) -> Box<dyn UploadCache + Send + Sync> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        Box::new(HttpUploadCache::new(location))
+    } else {
+        Box::new(DirectoryUploadCache::new(base_path.join(location)))
+    }
+}
+
+/// An upload cache stored as one JSON file per upload in a folder, which can be
+/// on a network share.
+pub struct DirectoryUploadCache {
+    path: PathBuf,
+}
+
+impl DirectoryUploadCache {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn file_path(&self, key: &UploadCacheKey) -> PathBuf {
+        self.path
+            .join(&key.creator)
+            .join(format!("{}.json", key.hash))
+    }
+}
+
+impl UploadCache for DirectoryUploadCache {
+    fn get(&self, key: &UploadCacheKey) -> Result<Option<CachedUpload>, UploadCacheError> {
+        let contents = match fs::read(self.file_path(key)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Some(serde_json::from_slice(&contents)?))
+    }
+
+    fn insert(&self, key: &UploadCacheKey, upload: CachedUpload) -> Result<(), UploadCacheError> {
+        let file_path = self.file_path(key);
+
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Writing to a temporary file first means that nobody reading the cache
+        // at the same time can see a partially written file.
+        let temp_path = file_path.with_extension(format!("{}.tmp", process::id()));
+        fs::write(&temp_path, serde_json::to_vec(&upload)?)?;
+        fs::rename(&temp_path, &file_path)?;
+
+        Ok(())
+    }
+}
+
+/// An upload cache stored in a key-value HTTP service. Uploads are read with
+/// `GET <url>/<creator>/<hash>`, which should respond with 404 if the upload
+/// isn't known, and written with `PUT` to the same URL.
+pub struct HttpUploadCache {
+    url: String,
+    client: Client,
+}
+
+impl HttpUploadCache {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_owned(),
+            client: Client::new(),
+        }
+    }
+
+    fn key_url(&self, key: &UploadCacheKey) -> String {
+        format!("{}/{}", self.url, key.relative_path())
+    }
+}
+
+impl UploadCache for HttpUploadCache {
+    fn get(&self, key: &UploadCacheKey) -> Result<Option<CachedUpload>, UploadCacheError> {
+        let url = self.key_url(key);
+        let mut response = self.client.get(&url).send()?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(response.json()?)),
+            status => Err(UploadCacheError::ResponseError { status, url }),
+        }
+    }
+
+    fn insert(&self, key: &UploadCacheKey, upload: CachedUpload) -> Result<(), UploadCacheError> {
+        let url = self.key_url(key);
+        let response = self.client.put(&url).json(&upload).send()?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(UploadCacheError::ResponseError { status, url }),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum UploadCacheError {
+    #[error("Upload cache returned HTTP {status} for {url}")]
+    ResponseError { status: StatusCode, url: String },
+
+    #[error(transparent)]
+    Http {
+        #[from]
+        source: reqwest::Error,
+    },
+
+    #[error(transparent)]
+    Json {
+        #[from]
+        source: serde_json::Error,
+    },
+
+    #[error(transparent)]
+    Io {
+        #[from]
+        source: io::Error,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn directory_cache(folder: &TempDir) -> DirectoryUploadCache {
+        DirectoryUploadCache::new(folder.join("cache"))
+    }
+
+    fn key(creator: &str, hash: &str) -> UploadCacheKey {
+        UploadCacheKey {
+            creator: creator.to_owned(),
+            hash: hash.to_owned(),
+        }
+    }
+
+    #[test]
+    fn directory_round_trip() {
+        let folder = TempDir::new("upload-cache-round-trip");
+        let cache = directory_cache(&folder);
+        let upload = CachedUpload {
+            id: 1234,
+            backing_id: Some(5678),
+        };
+
+        cache.insert(&key("group-1", "abc"), upload).unwrap();
+
+        assert_eq!(cache.get(&key("group-1", "abc")).unwrap(), Some(upload));
+    }
+
+    #[test]
+    fn directory_miss() {
+        let folder = TempDir::new("upload-cache-miss");
+        let cache = directory_cache(&folder);
+        let upload = CachedUpload {
+            id: 1234,
+            backing_id: None,
+        };
+
+        assert_eq!(cache.get(&key("group-1", "abc")).unwrap(), None);
+
+        // Uploads are only shared with the same creator.
+        cache.insert(&key("group-1", "abc"), upload).unwrap();
+        assert_eq!(cache.get(&key("group-2", "abc")).unwrap(), None);
+        assert_eq!(cache.get(&key("group-1", "def")).unwrap(), None);
+    }
+}